@@ -1,9 +1,18 @@
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
-use derive_utils::quick_derive;
+use derive_utils::{
+    EnumData, EnumDataOptions, EnumImplOptions, Fallback, MismatchedVariants, ProvidedMethods,
+    derive_trait, derive_trait_with, quick_derive,
+};
 use proc_macro::TokenStream;
+use quote::ToTokens as _;
+use syn::{
+    Ident, ItemTrait, Path,
+    parse::{ParseStream, Parser as _},
+    parse_macro_input, parse_quote,
+};
 
-#[proc_macro_derive(Iterator)]
+#[proc_macro_derive(Iterator, attributes(delegate, enum_derive))]
 pub fn derive_iterator(input: TokenStream) -> TokenStream {
     quick_derive! {
         input,
@@ -18,7 +27,7 @@ pub fn derive_iterator(input: TokenStream) -> TokenStream {
     }
 }
 
-#[proc_macro_derive(ExactSizeIterator)]
+#[proc_macro_derive(ExactSizeIterator, attributes(delegate, enum_derive))]
 pub fn derive_exact_size_iterator(input: TokenStream) -> TokenStream {
     quick_derive! {
         input,
@@ -33,6 +42,171 @@ pub fn derive_exact_size_iterator(input: TokenStream) -> TokenStream {
     }
 }
 
+#[proc_macro_derive(LendingIterator)]
+pub fn derive_lending_iterator(input: TokenStream) -> TokenStream {
+    quick_derive! {
+        input,
+        // trait path
+        LendingIterator,
+        // trait definition
+        trait LendingIterator {
+            type Item<'a>
+            where
+                Self: 'a;
+            fn next(&mut self) -> Option<Self::Item<'_>>;
+        }
+    }
+}
+
+#[proc_macro_derive(Parse)]
+pub fn derive_parse(input: TokenStream) -> TokenStream {
+    quick_derive! {
+        input,
+        // trait path
+        Parse,
+        // trait definition
+        trait Parse {
+            type Output<'a>;
+            fn parse<'a>(&self, input: &'a str) -> Self::Output<'a>;
+        }
+    }
+}
+
+#[proc_macro_derive(Resize, attributes(delegate))]
+pub fn derive_resize(input: TokenStream) -> TokenStream {
+    quick_derive! {
+        input,
+        // trait path
+        Resize,
+        // trait definition
+        trait Resize: Sized {
+            fn scaled(self, factor: u32) -> Self;
+            fn split(&self) -> Option<(Self, Self)>;
+            fn tiles(&self, n: usize) -> Vec<Self>;
+            fn checked(self, max: u32) -> Result<Self, (u32, Self)>;
+            fn area(&self) -> u32;
+        }
+    }
+}
+
+#[proc_macro_derive(BoxClone)]
+pub fn derive_box_clone(input: TokenStream) -> TokenStream {
+    quick_derive! {
+        input,
+        // trait path
+        BoxClone,
+        // trait definition
+        trait BoxClone {
+            fn box_clone(&self) -> Box<Self>;
+        }
+    }
+}
+
+#[proc_macro_derive(FirstTile)]
+pub fn derive_first_tile(input: TokenStream) -> TokenStream {
+    quick_derive! {
+        input,
+        // trait path
+        FirstTile,
+        // trait definition
+        trait FirstTile {
+            fn first_tile(&self) -> Option<<Vec<Self> as IntoIterator>::Item>;
+        }
+    }
+}
+
+#[proc_macro_derive(Future)]
+pub fn derive_future(input: TokenStream) -> TokenStream {
+    quick_derive! {
+        input,
+        // trait path
+        ::core::future::Future,
+        // trait definition
+        trait Future {
+            type Output;
+            fn poll(
+                self: ::core::pin::Pin<&mut Self>,
+                cx: &mut ::core::task::Context<'_>,
+            ) -> ::core::task::Poll<Self::Output>;
+        }
+    }
+}
+
+#[proc_macro_derive(PinnedLen)]
+pub fn derive_pinned_len(input: TokenStream) -> TokenStream {
+    quick_derive! {
+        input,
+        // trait path
+        PinnedLen,
+        // trait definition
+        trait PinnedLen {
+            fn len(self: ::core::pin::Pin<&Self>) -> usize;
+        }
+    }
+}
+
+/// Derives `PinnedName` for enums that also derive `Future`, which emits the
+/// `Unpin` impl.
+#[proc_macro_derive(PinnedName)]
+pub fn derive_pinned_name(input: TokenStream) -> TokenStream {
+    let data = parse_macro_input!(input as EnumData);
+    let options = EnumImplOptions::new().unpin_impl(false);
+    derive_trait_with(
+        &data,
+        &parse_quote!(PinnedName),
+        None,
+        parse_quote! {
+            trait PinnedName {
+                fn name(self: ::core::pin::Pin<&Self>) -> &'static str;
+            }
+        },
+        options,
+    )
+    .into()
+}
+
+#[proc_macro_derive(Task)]
+pub fn derive_task(input: TokenStream) -> TokenStream {
+    quick_derive! {
+        input,
+        // trait path
+        Task,
+        // trait definition
+        trait Task {
+            fn run(self: Box<Self>, input: u8) -> u8;
+        }
+    }
+}
+
+#[proc_macro_derive(Spawn)]
+pub fn derive_spawn(input: TokenStream) -> TokenStream {
+    quick_derive! {
+        input,
+        // trait path
+        Spawn,
+        // trait definition
+        trait Spawn {
+            fn spawn(self: std::sync::Arc<Self>);
+            fn name(&self) -> &'static str;
+        }
+    }
+}
+
+#[proc_macro_derive(Source)]
+pub fn derive_source(input: TokenStream) -> TokenStream {
+    quick_derive! {
+        input,
+        // trait path
+        Source,
+        // trait definition
+        trait Source {
+            async fn fetch(&self, offset: u8) -> u8;
+            fn values(&self) -> impl DoubleEndedIterator<Item = u8> + ExactSizeIterator + '_;
+            fn ready(&self) -> impl ::core::future::Future<Output = u8> + Send;
+        }
+    }
+}
+
 #[proc_macro_derive(MyTrait1)]
 pub fn derive_my_trait1(input: TokenStream) -> TokenStream {
     quick_derive! {
@@ -59,3 +233,245 @@ pub fn derive_my_trait2(input: TokenStream) -> TokenStream {
         trait MyTrait2: MyTrait1 {}
     }
 }
+
+#[proc_macro_derive(DefaultIterator)]
+pub fn derive_default_iterator(input: TokenStream) -> TokenStream {
+    // Variants with zero fields behave like an empty iterator.
+    let options = EnumDataOptions::new().unit_variants(Fallback::Default).discriminants(true);
+    let data = match parse_with(input, options) {
+        Ok(data) => data,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    derive_trait(&data, &parse_quote!(std::iter::Iterator), None, parse_quote! {
+        trait Iterator {
+            type Item;
+            fn next(&mut self) -> Option<Self::Item>;
+            fn size_hint(&self) -> (usize, Option<usize>);
+        }
+    })
+    .into()
+}
+
+#[proc_macro_derive(Len, attributes(inner))]
+pub fn derive_len(input: TokenStream) -> TokenStream {
+    let options = EnumDataOptions::new()
+        .unit_variants(Fallback::Expr(parse_quote!(0)))
+        .empty(true)
+        .delegate_attr("inner");
+    let data = match parse_with(input, options) {
+        Ok(data) => data,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    derive_trait(&data, &parse_quote!(Len), None, parse_quote! {
+        trait Len {
+            fn len(&self) -> usize;
+        }
+    })
+    .into()
+}
+
+#[proc_macro_derive(SkipIterator)]
+pub fn derive_skip_iterator(input: TokenStream) -> TokenStream {
+    derive_full_iterator(input, ProvidedMethods::Skip)
+}
+
+#[proc_macro_derive(ForwardIterator)]
+pub fn derive_forward_iterator(input: TokenStream) -> TokenStream {
+    derive_full_iterator(input, ProvidedMethods::Forward)
+}
+
+/// Derives `Iterator` from a trait definition that includes provided methods.
+fn derive_full_iterator(input: TokenStream, mode: ProvidedMethods) -> TokenStream {
+    let data = parse_macro_input!(input as EnumData);
+    let options = EnumImplOptions::new().provided_methods(mode);
+    derive_trait_with(
+        &data,
+        &parse_quote!(std::iter::Iterator),
+        None,
+        parse_quote! {
+            trait Iterator {
+                type Item;
+                fn next(&mut self) -> Option<<Self as Iterator>::Item>;
+                fn size_hint(&self) -> (usize, Option<usize>) {
+                    (0, None)
+                }
+                fn nth(&mut self, n: usize) -> Option<<Self as Iterator>::Item> {
+                    unimplemented!()
+                }
+                fn fold<B, F>(self, init: B, f: F) -> B
+                where
+                    Self: Sized,
+                    F: FnMut(B, Self::Item) -> B,
+                {
+                    unimplemented!()
+                }
+                fn by_ref(&mut self) -> &mut Self
+                where
+                    Self: Sized,
+                {
+                    self
+                }
+            }
+        },
+        options,
+    )
+    .into()
+}
+
+#[proc_macro_derive(Name)]
+pub fn derive_name(input: TokenStream) -> TokenStream {
+    let data = parse_macro_input!(input as EnumData);
+    let options = EnumImplOptions::new()
+        .check_assoc_consts(true)
+        .assoc_const_value("VERSION", parse_quote!(1));
+    derive_trait_with(
+        &data,
+        &parse_quote!(Name),
+        None,
+        parse_quote! {
+            trait Name {
+                const NAME: &'static str;
+                const ID: u32;
+                const VERSION: u8;
+                fn name(&self) -> &'static str;
+            }
+        },
+        options,
+    )
+    .into()
+}
+
+#[proc_macro_derive(PartialEq)]
+pub fn derive_partial_eq(input: TokenStream) -> TokenStream {
+    let data = parse_macro_input!(input as EnumData);
+    let options = EnumImplOptions::new().mismatched_variants(
+        "eq",
+        MismatchedVariants::Fallback(Fallback::Expr(parse_quote!(false))),
+    );
+    derive_trait_with(
+        &data,
+        &parse_quote!(::core::cmp::PartialEq),
+        None,
+        parse_quote! {
+            trait PartialEq {
+                fn eq(&self, other: &Self) -> bool;
+            }
+        },
+        options,
+    )
+    .into()
+}
+
+#[proc_macro_derive(PartialOrd)]
+pub fn derive_partial_ord(input: TokenStream) -> TokenStream {
+    let data = parse_macro_input!(input as EnumData);
+    let options = EnumImplOptions::new()
+        .mismatched_variants("partial_cmp", MismatchedVariants::CompareIndices);
+    derive_trait_with(
+        &data,
+        &parse_quote!(::core::cmp::PartialOrd),
+        None,
+        parse_quote! {
+            trait PartialOrd: PartialEq {
+                fn partial_cmp(&self, other: &Self) -> Option<::core::cmp::Ordering>;
+            }
+        },
+        options,
+    )
+    .into()
+}
+
+#[proc_macro_derive(Merge)]
+pub fn derive_merge(input: TokenStream) -> TokenStream {
+    quick_derive! {
+        input,
+        // trait path
+        Merge,
+        // trait definition
+        trait Merge {
+            fn merge(&mut self, other: Self);
+            fn merged(self, other: Self) -> Self;
+        }
+    }
+}
+
+#[proc_macro_derive(IntoIterator)]
+pub fn derive_into_iterator(input: TokenStream) -> TokenStream {
+    let data = parse_macro_input!(input as EnumData);
+    let options = EnumImplOptions::new().assoc_type_enum("IntoIter");
+    derive_trait_with(
+        &data,
+        &parse_quote!(::core::iter::IntoIterator),
+        None,
+        parse_quote! {
+            trait IntoIterator {
+                type Item;
+                type IntoIter: Iterator<Item = Self::Item>;
+                fn into_iter(self) -> Self::IntoIter;
+            }
+        },
+        options,
+    )
+    .into()
+}
+
+#[proc_macro_derive(Decode)]
+pub fn derive_decode(input: TokenStream) -> TokenStream {
+    let data = parse_macro_input!(input as EnumData);
+    let options = EnumImplOptions::new().assoc_type_target("Error", parse_quote!(DecodeError));
+    derive_trait_with(
+        &data,
+        &parse_quote!(Decode),
+        None,
+        parse_quote! {
+            trait Decode: Sized {
+                type Error;
+                fn decode(&self, input: &[u8]) -> Result<u8, Self::Error>;
+                fn limit(self, max: u8) -> Result<Self, Self::Error>;
+                fn error(&self) -> Self::Error;
+            }
+        },
+        options,
+    )
+    .into()
+}
+
+/// Implements a trait like the derives above, but as an attribute macro, which
+/// sees the `#[cfg]` attributes of variants before they are evaluated.
+#[proc_macro_attribute]
+pub fn enum_impl(args: TokenStream, input: TokenStream) -> TokenStream {
+    let name = parse_macro_input!(args as Ident);
+    let data = parse_macro_input!(input as EnumData);
+    let mut options = EnumImplOptions::new();
+    let (trait_path, trait_def): (Path, ItemTrait) = match name.to_string().as_str() {
+        "Iterator" => (parse_quote!(::core::iter::Iterator), parse_quote! {
+            trait Iterator {
+                type Item;
+                fn next(&mut self) -> Option<Self::Item>;
+            }
+        }),
+        "IntoIterator" => {
+            options = options.assoc_type_enum("IntoIter");
+            (parse_quote!(::core::iter::IntoIterator), parse_quote! {
+                trait IntoIterator {
+                    type Item;
+                    type IntoIter: Iterator<Item = Self::Item>;
+                    fn into_iter(self) -> Self::IntoIter;
+                }
+            })
+        }
+        "Values" => (parse_quote!(Values), parse_quote! {
+            trait Values {
+                fn values(&self) -> impl Iterator<Item = u8> + '_;
+            }
+        }),
+        _ => return syn::Error::new(name.span(), "unsupported trait").to_compile_error().into(),
+    };
+    let mut tokens = data.to_token_stream();
+    tokens.extend(derive_trait_with(&data, &trait_path, None, trait_def, options));
+    tokens.into()
+}
+
+fn parse_with(input: TokenStream, options: EnumDataOptions) -> syn::Result<EnumData> {
+    (|input: ParseStream<'_>| EnumData::parse_with(input, options)).parse(input)
+}
@@ -1,36 +1,319 @@
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
-use alloc::vec::Vec;
+use alloc::{collections::BTreeSet, format, string::String, vec, vec::Vec};
 use core::ops;
 
-use proc_macro2::TokenStream;
-use quote::ToTokens;
+use proc_macro2::{Span, TokenStream};
+use quote::{ToTokens, format_ident, quote};
 use syn::{
-    Fields, Ident, ItemEnum, Result, Type,
+    Attribute, DeriveInput, Error, Expr, Fields, GenericParam, Generics, Ident, Index, ItemEnum,
+    ItemStruct, LitStr, Member, Meta, Result, Token, Type, Variant, Visibility, WherePredicate,
     parse::{Parse, ParseStream},
+    parse_quote,
+    punctuated::Punctuated,
+    spanned::Spanned as _,
+    token,
 };
 
+use crate::ty::{self, TypeKey};
+
 /// A structure to make trait implementation to enums more efficient.
+///
+/// Each variant must have exactly one field, unless one of its fields is marked
+/// with the `#[delegate]` attribute. Derive macros that accept the latter need
+/// to declare `delegate` as a helper attribute.
+///
+/// Variants marked with `#[enum_derive(skip)]` are not delegated to, and can
+/// have any fields. Methods evaluate the fallback specified in the attribute
+/// for them instead:
+///
+/// - `#[enum_derive(skip)]` or `#[enum_derive(skip(unreachable))]`: `unreachable!()`
+/// - `#[enum_derive(skip(panic = "message"))]`: panics with the message
+/// - `#[enum_derive(skip(default))]`: `Default::default()`
+/// - `#[enum_derive(skip(with = expr))]`: `expr`
+///
+/// Use [`EnumData::parse_with`] to accept other enum shapes.
+///
+/// ```text
+/// #[derive(Iterator)]
+/// enum Enum<A, B, C> {
+///     A(A, Span),
+///     B(Span, #[delegate] B),
+///     C { #[delegate] inner: C, span: Span },
+///     #[enum_derive(skip(panic = "closed"))]
+///     Closed(Span, Reason),
+/// }
+/// ```
 pub struct EnumData {
     repr: ItemEnum,
     field_types: Vec<Type>,
+    /// How each variant is delegated.
+    delegates: Vec<Delegate>,
+    /// The `#[cfg]` attributes of each variant, including those in
+    /// `#[cfg_attr]`.
+    cfg_attrs: Vec<Vec<Attribute>>,
+    unit_fallback: Option<Fallback>,
+}
+
+/// How methods handle a variant of an [`EnumData`].
+enum Delegate {
+    /// Delegates to the field, with the index of its type in `field_types`.
+    Field(Member, usize),
+    /// Evaluates the fallback for variants with zero fields.
+    Unit,
+    /// Evaluates the fallback of a variant marked with `#[enum_derive(skip)]`.
+    Skip(Fallback),
 }
 
 impl EnumData {
+    /// Creates a builder for an enum whose variants each have one field.
+    ///
+    /// This is useful for creating an `EnumData` for a generated enum
+    /// without parsing its tokens.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use derive_utils::EnumData;
+    /// use quote::{ToTokens as _, format_ident};
+    /// use syn::parse_quote;
+    ///
+    /// let data = EnumData::builder(format_ident!("Enum"))
+    ///     .generic_param(parse_quote!(T))
+    ///     .variant(format_ident!("A"), parse_quote!(T))
+    ///     .variant(format_ident!("B"), parse_quote!(Vec<u8>))
+    ///     .build();
+    /// assert_eq!(data.to_token_stream().to_string(), "enum Enum < T > { A (T) , B (Vec < u8 >) }");
+    /// ```
+    pub fn builder(ident: Ident) -> EnumDataBuilder {
+        EnumDataBuilder {
+            attrs: vec![],
+            vis: Visibility::Inherited,
+            ident,
+            generics: Generics::default(),
+            variants: vec![],
+        }
+    }
+
+    /// Creates an enum with `variants` variants, each of which has a field of
+    /// its own type parameter.
+    ///
+    /// Type parameters are named `__T0`, `__T1`, ..., and variants are named
+    /// `__V0`, `__V1`, .... The enum itself can be emitted via the
+    /// [`ToTokens`] implementation of `EnumData`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use derive_utils::EnumData;
+    /// use quote::{ToTokens as _, format_ident};
+    ///
+    /// let data = EnumData::anonymous(format_ident!("__Enum"), 2);
+    /// assert_eq!(
+    ///     data.to_token_stream().to_string(),
+    ///     "enum __Enum < __T0 , __T1 > { __V0 (__T0) , __V1 (__T1) }",
+    /// );
+    /// assert_eq!(data.variant_idents().len(), 2);
+    /// ```
+    pub fn anonymous(ident: Ident, variants: usize) -> Self {
+        Self::anonymous_builder(ident, variants).build()
+    }
+
+    /// Returns a builder that has the parameters and variants of
+    /// [`EnumData::anonymous`].
+    pub(crate) fn anonymous_builder(ident: Ident, variants: usize) -> EnumDataBuilder {
+        (0..variants).fold(Self::builder(ident), |builder, i| {
+            let ty = format_ident!("__T{}", i);
+            builder
+                .generic_param(parse_quote!(#ty))
+                .variant(format_ident!("__V{}", i), parse_quote!(#ty))
+        })
+    }
+
+    /// Parses an enum with the given options.
+    ///
+    /// The [`Parse`] implementation of `EnumData` is equivalent to calling this
+    /// with the default options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use derive_utils::{EnumData, EnumDataOptions, Fallback};
+    /// use syn::parse::Parser as _;
+    ///
+    /// let data = (|input: syn::parse::ParseStream<'_>| {
+    ///     EnumData::parse_with(input, EnumDataOptions::new().unit_variants(Fallback::Unreachable))
+    /// })
+    /// .parse_str("enum Source<A, B> { Empty, File(A), Net(B) }")
+    /// .unwrap();
+    /// assert_eq!(data.field_types().len(), 2);
+    /// assert_eq!(data.variant_idents().len(), 3);
+    /// ```
+    pub fn parse_with(input: ParseStream<'_>, options: EnumDataOptions) -> Result<Self> {
+        Self::from_item_with(input.parse()?, options)
+    }
+
+    /// Creates a new `EnumData` from an already parsed enum with the given
+    /// options.
+    ///
+    /// This performs the same validation as [`EnumData::parse_with`]. Use
+    /// `EnumData::try_from` to create one with the default options from an
+    /// [`ItemEnum`] or a [`DeriveInput`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use derive_utils::EnumData;
+    /// use syn::{DeriveInput, parse_quote};
+    ///
+    /// let input: DeriveInput = parse_quote!(
+    ///     enum Enum<A, B> {
+    ///         A(A),
+    ///         B(B),
+    ///     }
+    /// );
+    /// let data = EnumData::try_from(input).unwrap();
+    /// assert_eq!(data.field_types().len(), 2);
+    ///
+    /// let input: DeriveInput = parse_quote!(
+    ///     struct Struct<A>(A);
+    /// );
+    /// let err = EnumData::try_from(input).err().unwrap();
+    /// assert_eq!(err.to_string(), "may not be used on structs");
+    /// ```
+    pub fn from_item_with(item: ItemEnum, options: EnumDataOptions) -> Result<Self> {
+        if item.variants.is_empty() && !options.empty {
+            bail!(item, "may not be used on enums without variants");
+        }
+
+        // Collect errors for all variants instead of returning at the first one,
+        // so that a single compile reports all problems.
+        let mut error: Option<Error> = None;
+        let mut push_error = |e: Error| match &mut error {
+            Some(error) => error.combine(e),
+            None => error = Some(e),
+        };
+        let mut field_types = Vec::with_capacity(item.variants.len());
+        let mut delegates = Vec::with_capacity(item.variants.len());
+        let mut cfg_attrs = Vec::with_capacity(item.variants.len());
+        for v in &item.variants {
+            cfg_attrs.push(variant_cfg_attrs(&v.attrs).unwrap_or_else(|e| {
+                push_error(e);
+                vec![]
+            }));
+
+            match &v.discriminant {
+                Some((_, e)) if !options.discriminants => {
+                    push_error(format_err!(e, "may not be used on enums with discriminants"));
+                }
+                _ => {}
+            }
+
+            match skip_fallback(&v.attrs, options.skip_attr_name()) {
+                Ok(Some(fallback)) => {
+                    delegates.push(Delegate::Skip(fallback));
+                    continue;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    push_error(e);
+                    continue;
+                }
+            }
+
+            if v.fields.is_empty() {
+                if options.unit_variants.is_some() {
+                    delegates.push(Delegate::Unit);
+                } else {
+                    push_error(format_err!(
+                        v,
+                        "may not be used on enums with variants with zero fields"
+                    ));
+                }
+                continue;
+            }
+            match delegate_field(&v.fields, options.delegate_attr_name()) {
+                Ok(Some((member, ty))) => {
+                    delegates.push(Delegate::Field(member, field_types.len()));
+                    field_types.push(ty.clone());
+                }
+                Ok(None) => push_error(format_err!(
+                    v,
+                    "may not be used on enums with variants with multiple fields"
+                )),
+                Err(e) => push_error(e),
+            }
+        }
+        if let Some(e) = error {
+            return Err(e);
+        }
+
+        if field_types.is_empty() && !options.empty {
+            bail!(item, "may not be used on enums without variants with fields");
+        }
+
+        Ok(Self {
+            repr: item,
+            field_types,
+            delegates,
+            cfg_attrs,
+            unit_fallback: options.unit_variants,
+        })
+    }
+
     /// Returns an iterator over field types.
     ///
+    /// Variants with zero fields and variants marked with `#[enum_derive(skip)]`
+    /// are skipped.
+    ///
     /// ```text
-    /// enum Enum<TypeA, TypeB> {
+    /// enum Enum<TypeA, TypeB, TypeC> {
     ///     VariantA(TypeA),
     ///              ^^^^^
     ///     VariantB(TypeB),
     ///              ^^^^^
+    ///     VariantC,
+    ///     #[enum_derive(skip)]
+    ///     VariantD(TypeC),
     /// }
     /// ```
     pub fn field_types(&self) -> impl ExactSizeIterator<Item = &Type> + Clone {
         self.field_types.iter()
     }
 
+    /// Returns an iterator over field types, skipping types that are the same
+    /// as a previous one.
+    ///
+    /// Types are compared structurally, so equivalent spellings such as
+    /// `Vec<T>` and `::std::vec::Vec<T>` are considered the same. `Self` is
+    /// considered the same as the enum type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use derive_utils::EnumData;
+    /// use syn::parse_quote;
+    ///
+    /// let data: EnumData = parse_quote! {
+    ///     enum Enum<T> {
+    ///         A(Vec<T>),
+    ///         B(::std::vec::Vec<T>),
+    ///         C(alloc::vec::Vec<(T)>),
+    ///         D(Box<Self>),
+    ///         E(Box<Enum<T>>),
+    ///     }
+    /// };
+    /// assert_eq!(data.field_types().len(), 5);
+    /// assert_eq!(data.unique_field_types().count(), 2);
+    /// ```
+    pub fn unique_field_types(&self) -> impl Iterator<Item = &Type> + Clone {
+        let self_ty = self_ty(&self.ident, &self.generics);
+        let mut seen = BTreeSet::new();
+        let unique: Vec<_> =
+            self.field_types.iter().filter(|ty| seen.insert(TypeKey::new(ty, &self_ty))).collect();
+        unique.into_iter()
+    }
+
     /// Returns an iterator over variant names.
     ///
     /// ```text
@@ -44,6 +327,54 @@ impl EnumData {
     pub fn variant_idents(&self) -> impl ExactSizeIterator<Item = &Ident> + Clone {
         self.variants.iter().map(|v| &v.ident)
     }
+
+    /// Returns an iterator over variants.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use derive_utils::EnumData;
+    /// use quote::ToTokens as _;
+    /// use syn::parse_quote;
+    ///
+    /// let data: EnumData = parse_quote! {
+    ///     enum Enum<A, B> {
+    ///         A(A),
+    ///         #[cfg(feature = "b")]
+    ///         B { #[delegate] inner: B, span: Span },
+    ///         #[cfg_attr(unix, cfg(feature = "c"))]
+    ///         C(A),
+    ///     }
+    /// };
+    /// let b = data.variants().nth(1).unwrap();
+    /// assert_eq!(b.ident(), "B");
+    /// assert_eq!(b.field_type().unwrap().to_token_stream().to_string(), "B");
+    /// assert_eq!(b.member().unwrap().to_token_stream().to_string(), "inner");
+    /// assert_eq!(b.cfg_attrs().count(), 1);
+    /// let c = data.variants().nth(2).unwrap();
+    /// assert_eq!(
+    ///     c.cfg_attrs().next().unwrap().to_token_stream().to_string(),
+    ///     "# [cfg (any (not (all (unix)) , feature = \"c\"))]",
+    /// );
+    /// ```
+    pub fn variants(&self) -> impl ExactSizeIterator<Item = VariantData<'_>> + Clone {
+        self.variants.iter().zip(&self.delegates).zip(&self.cfg_attrs).map(
+            |((variant, delegate), cfg_attrs)| {
+                let (field, fallback) = match delegate {
+                    Delegate::Field(member, i) => (Some((member, &self.field_types[*i])), None),
+                    Delegate::Unit => (None, self.unit_fallback.as_ref()),
+                    Delegate::Skip(fallback) => (None, Some(fallback)),
+                };
+                VariantData {
+                    variant,
+                    field,
+                    fallback,
+                    skipped: matches!(delegate, Delegate::Skip(_)),
+                    cfg_attrs,
+                }
+            },
+        )
+    }
 }
 
 impl ops::Deref for EnumData {
@@ -62,44 +393,708 @@ impl From<EnumData> for ItemEnum {
 
 impl Parse for EnumData {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
-        let item: ItemEnum = input.parse()?;
+        Self::parse_with(input, EnumDataOptions::new())
+    }
+}
 
-        if item.variants.is_empty() {
-            bail!(item, "may not be used on enums without variants");
+impl TryFrom<ItemEnum> for EnumData {
+    type Error = Error;
+
+    fn try_from(item: ItemEnum) -> Result<Self> {
+        Self::from_item_with(item, EnumDataOptions::new())
+    }
+}
+
+impl TryFrom<DeriveInput> for EnumData {
+    type Error = Error;
+
+    fn try_from(input: DeriveInput) -> Result<Self> {
+        let data = match input.data {
+            syn::Data::Enum(data) => data,
+            syn::Data::Struct(data) => bail!(data.struct_token, "may not be used on structs"),
+            syn::Data::Union(data) => bail!(data.union_token, "may not be used on unions"),
+        };
+        Self::try_from(ItemEnum {
+            attrs: input.attrs,
+            vis: input.vis,
+            enum_token: data.enum_token,
+            ident: input.ident,
+            generics: input.generics,
+            brace_token: data.brace_token,
+            variants: data.variants,
+        })
+    }
+}
+
+impl ToTokens for EnumData {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.repr.to_tokens(tokens);
+    }
+}
+
+/// A builder for [`EnumData`].
+///
+/// This is created by [`EnumData::builder`].
+pub struct EnumDataBuilder {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    ident: Ident,
+    generics: Generics,
+    variants: Vec<(Ident, Type)>,
+}
+
+impl EnumDataBuilder {
+    /// Appends an attribute to the enum.
+    #[must_use]
+    pub fn attr(mut self, attr: Attribute) -> Self {
+        self.attrs.push(attr);
+        self
+    }
+
+    /// Sets the visibility of the enum.
+    ///
+    /// The default is private.
+    #[must_use]
+    pub fn vis(mut self, vis: Visibility) -> Self {
+        self.vis = vis;
+        self
+    }
+
+    /// Appends a generic parameter to the enum.
+    #[must_use]
+    pub fn generic_param(mut self, param: GenericParam) -> Self {
+        self.generics.params.push(param);
+        self
+    }
+
+    /// Appends a predicate to the `where`-clause of the enum.
+    #[must_use]
+    pub fn where_predicate(mut self, predicate: WherePredicate) -> Self {
+        self.generics.make_where_clause().predicates.push(predicate);
+        self
+    }
+
+    /// Appends a variant with a single field of type `ty`.
+    #[must_use]
+    pub fn variant(mut self, ident: Ident, ty: Type) -> Self {
+        self.variants.push((ident, ty));
+        self
+    }
+
+    /// Builds the `EnumData`.
+    pub fn build(self) -> EnumData {
+        let delegates = (0..self.variants.len())
+            .map(|i| Delegate::Field(Member::Unnamed(Index::from(0)), i))
+            .collect();
+        let cfg_attrs = vec![vec![]; self.variants.len()];
+        let mut field_types = Vec::with_capacity(self.variants.len());
+        let variants = self
+            .variants
+            .into_iter()
+            .map(|(ident, ty)| {
+                let variant: Variant = parse_quote!(#ident(#ty));
+                field_types.push(ty);
+                variant
+            })
+            .collect();
+        let repr = ItemEnum {
+            attrs: self.attrs,
+            vis: self.vis,
+            enum_token: <Token![enum]>::default(),
+            ident: self.ident,
+            generics: self.generics,
+            brace_token: token::Brace::default(),
+            variants,
+        };
+        EnumData { repr, field_types, delegates, cfg_attrs, unit_fallback: None }
+    }
+}
+
+/// A variant of an [`EnumData`].
+#[derive(Clone, Copy)]
+pub struct VariantData<'a> {
+    variant: &'a Variant,
+    field: Option<(&'a Member, &'a Type)>,
+    fallback: Option<&'a Fallback>,
+    skipped: bool,
+    cfg_attrs: &'a [Attribute],
+}
+
+impl<'a> VariantData<'a> {
+    /// Returns the name of the variant.
+    pub fn ident(&self) -> &'a Ident {
+        &self.variant.ident
+    }
+
+    /// Returns the attributes of the variant.
+    pub fn attrs(&self) -> &'a [Attribute] {
+        &self.variant.attrs
+    }
+
+    /// Returns the type of the field to delegate to, or `None` if the variant
+    /// has zero fields or is skipped.
+    pub fn field_type(&self) -> Option<&'a Type> {
+        self.field.map(|(_, ty)| ty)
+    }
+
+    /// Returns the name or index of the field to delegate to, or `None` if the
+    /// variant has zero fields or is skipped.
+    pub fn member(&self) -> Option<&'a Member> {
+        self.field.map(|(member, _)| member)
+    }
+
+    /// Returns `true` if the variant is marked with `#[enum_derive(skip)]`.
+    pub fn is_skipped(&self) -> bool {
+        self.skipped
+    }
+
+    /// Returns the expression evaluated in place of delegating, or `None` if
+    /// the variant has a field to delegate to.
+    pub fn fallback(&self) -> Option<&'a Fallback> {
+        self.fallback
+    }
+
+    /// Returns the span of the variant.
+    pub fn span(&self) -> Span {
+        self.variant.span()
+    }
+
+    /// Returns an iterator over `#[cfg]` attributes of the variant.
+    ///
+    /// `#[cfg]` attributes in `#[cfg_attr]` are included with the predicates
+    /// combined, e.g., `#[cfg_attr(a, cfg(b))]` is returned as
+    /// `#[cfg(any(not(all(a)), b))]`. Other attributes in `#[cfg_attr]` are not included
+    /// because they are not necessarily valid in other positions.
+    pub fn cfg_attrs(&self) -> impl Iterator<Item = &'a Attribute> + Clone {
+        self.cfg_attrs.iter()
+    }
+}
+
+/// A structure to make trait implementation to newtype structs more efficient.
+///
+/// The struct must have exactly one field, unless one of its fields is marked
+/// with the `#[delegate]` attribute.
+///
+/// ```text
+/// #[derive(Iterator)]
+/// struct Wrapper<I>(I);
+///
+/// #[derive(Iterator)]
+/// struct Spanned<I> {
+///     #[delegate]
+///     inner: I,
+///     span: Span,
+/// }
+/// ```
+pub struct StructData {
+    repr: ItemStruct,
+    member: Member,
+    field_type: Type,
+}
+
+impl StructData {
+    /// Returns the type of the field to delegate to.
+    ///
+    /// ```text
+    /// struct Struct<Type>(Type);
+    ///                     ^^^^
+    /// ```
+    pub fn field_type(&self) -> &Type {
+        &self.field_type
+    }
+
+    /// Returns the name or index of the field to delegate to.
+    ///
+    /// ```text
+    /// struct Struct<Type> {
+    ///     field: Type,
+    ///     ^^^^^
+    /// }
+    /// ```
+    pub fn member(&self) -> &Member {
+        &self.member
+    }
+}
+
+impl ops::Deref for StructData {
+    type Target = ItemStruct;
+
+    fn deref(&self) -> &Self::Target {
+        &self.repr
+    }
+}
+
+impl From<StructData> for ItemStruct {
+    fn from(other: StructData) -> Self {
+        other.repr
+    }
+}
+
+impl StructData {
+    /// Parses a struct with the given options.
+    ///
+    /// Only [`EnumDataOptions::delegate_attr`] affects structs.
+    pub fn parse_with(input: ParseStream<'_>, options: EnumDataOptions) -> Result<Self> {
+        Self::from_item_with(input.parse()?, options)
+    }
+
+    /// Creates a new `StructData` from an already parsed struct with the given
+    /// options.
+    ///
+    /// This performs the same validation as [`StructData::parse_with`].
+    #[allow(clippy::needless_pass_by_value)] // for consistency with EnumData::from_item_with
+    pub fn from_item_with(item: ItemStruct, options: EnumDataOptions) -> Result<Self> {
+        if item.fields.is_empty() {
+            bail!(item, "may not be used on structs without fields");
         }
 
-        let field_types = item.variants.iter().try_fold(
-            Vec::with_capacity(item.variants.len()),
-            |mut field_types, v| {
-                if let Some((_, e)) = &v.discriminant {
-                    bail!(e, "may not be used on enums with discriminants");
-                }
+        let (member, field_type) = match delegate_field(&item.fields, options.delegate_attr_name())?
+        {
+            Some((member, ty)) => (member, ty.clone()),
+            None => bail!(item.fields, "may not be used on structs with multiple fields"),
+        };
+
+        Ok(Self { repr: item, member, field_type })
+    }
+}
+
+impl Parse for StructData {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        Self::parse_with(input, EnumDataOptions::new())
+    }
+}
+
+impl TryFrom<ItemStruct> for StructData {
+    type Error = Error;
+
+    fn try_from(item: ItemStruct) -> Result<Self> {
+        Self::from_item_with(item, EnumDataOptions::new())
+    }
+}
+
+impl TryFrom<DeriveInput> for StructData {
+    type Error = Error;
+
+    fn try_from(input: DeriveInput) -> Result<Self> {
+        let data = match input.data {
+            syn::Data::Struct(data) => data,
+            syn::Data::Enum(data) => bail!(data.enum_token, "may not be used on enums"),
+            syn::Data::Union(data) => bail!(data.union_token, "may not be used on unions"),
+        };
+        Self::try_from(ItemStruct {
+            attrs: input.attrs,
+            vis: input.vis,
+            struct_token: data.struct_token,
+            ident: input.ident,
+            generics: input.generics,
+            fields: data.fields,
+            semi_token: data.semi_token,
+        })
+    }
+}
+
+impl ToTokens for StructData {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.repr.to_tokens(tokens);
+    }
+}
+
+/// Returns the type of an enum or struct, e.g., `Enum<A, B>`.
+pub(crate) fn self_ty(ident: &Ident, generics: &Generics) -> Type {
+    let ty_generics = generics.split_for_impl().1;
+    parse_quote!(#ident #ty_generics)
+}
+
+/// The input of [`EnumImpl`](crate::EnumImpl).
+#[derive(Clone, Copy)]
+pub(crate) enum Data<'a> {
+    Enum(&'a EnumData),
+    Struct(&'a StructData),
+}
+
+impl<'a> Data<'a> {
+    pub(crate) fn ident(self) -> &'a Ident {
+        match self {
+            Self::Enum(data) => &data.ident,
+            Self::Struct(data) => &data.ident,
+        }
+    }
+
+    pub(crate) fn generics(self) -> &'a Generics {
+        match self {
+            Self::Enum(data) => &data.generics,
+            Self::Struct(data) => &data.generics,
+        }
+    }
+
+    pub(crate) fn vis(self) -> &'a Visibility {
+        match self {
+            Self::Enum(data) => &data.vis,
+            Self::Struct(data) => &data.vis,
+        }
+    }
+
+    /// Returns the types of the fields to delegate to and the `#[cfg]`
+    /// attributes of their variants.
+    pub(crate) fn fields(self) -> Vec<(&'a Type, Vec<&'a Attribute>)> {
+        self.arms().into_iter().filter_map(|arm| Some((arm.field?.1, arm.cfg_attrs))).collect()
+    }
+
+    /// Returns the type of the field to delegate to of the first variant that
+    /// is not gated by `#[cfg]`, or of the first variant if all are gated.
+    ///
+    /// Types that contain the self type (e.g., `Box<Self>`) are only returned
+    /// if all types do, since their associated types refer back to the impl.
+    pub(crate) fn first_field_type(self) -> Option<&'a Type> {
+        let self_ty = self_ty(self.ident(), self.generics());
+        let mut fields = self.fields();
+        if fields.iter().any(|(ty, _)| !ty::contains_self_type(ty, &self_ty)) {
+            fields.retain(|(ty, _)| !ty::contains_self_type(ty, &self_ty));
+        }
+        fields.iter().find(|(_, cfg_attrs)| cfg_attrs.is_empty()).or(fields.first()).map(|f| f.0)
+    }
+
+    /// Returns the match arms of a method, one per variant.
+    pub(crate) fn arms(self) -> Vec<Arm<'a>> {
+        match self {
+            Self::Enum(data) => {
+                let ident = &data.ident;
+                data.variants()
+                    .map(|v| {
+                        let variant = v.ident();
+                        Arm {
+                            path: quote!(#ident::#variant),
+                            fields: &v.variant.fields,
+                            field: v.field,
+                            fallback: v.fallback,
+                            cfg_attrs: v.cfg_attrs().collect(),
+                        }
+                    })
+                    .collect()
+            }
+            Self::Struct(data) => {
+                let ident = &data.ident;
+                vec![Arm {
+                    path: quote!(#ident),
+                    fields: &data.fields,
+                    field: Some((&data.member, &data.field_type)),
+                    fallback: None,
+                    cfg_attrs: vec![],
+                }]
+            }
+        }
+    }
+}
+
+/// A match arm of a generated method.
+pub(crate) struct Arm<'a> {
+    /// The path to the variant or struct.
+    path: TokenStream,
+    fields: &'a Fields,
+    /// The field to delegate to, or `None` for variants with zero fields and
+    /// skipped variants.
+    pub(crate) field: Option<(&'a Member, &'a Type)>,
+    /// The expression evaluated if `field` is `None`.
+    pub(crate) fallback: Option<&'a Fallback>,
+    /// The `#[cfg]` attributes of the variant.
+    pub(crate) cfg_attrs: Vec<&'a Attribute>,
+}
+
+impl Arm<'_> {
+    /// Generates a pattern that binds the field to delegate to `binding`.
+    pub(crate) fn pat(&self, binding: &Ident) -> TokenStream {
+        let path = &self.path;
+        match self.field {
+            None => quote!(#path { .. }),
+            Some((Member::Unnamed(_), _)) if self.fields.len() == 1 => quote!(#path(#binding)),
+            Some((Member::Unnamed(index), _)) => {
+                let skip = (0..index.index).map(|_| quote!(_,));
+                quote!(#path(#(#skip)* #binding, ..))
+            }
+            Some((Member::Named(field), _)) => quote!(#path { #field: #binding, .. }),
+        }
+    }
+
+    /// Generates a pattern that matches the variant without binding fields.
+    pub(crate) fn pat_rest(&self) -> TokenStream {
+        let path = &self.path;
+        quote!(#path { .. })
+    }
 
-                if v.fields.is_empty() {
-                    bail!(v, "may not be used on enums with variants with zero fields");
-                } else if v.fields.len() != 1 {
-                    bail!(v, "may not be used on enums with variants with multiple fields");
+    /// Generates an expression that constructs the variant from `expr`, the
+    /// value of the field to delegate to.
+    ///
+    /// Returns `None` if the variant has other fields.
+    pub(crate) fn wrap(&self, expr: &TokenStream) -> Option<TokenStream> {
+        let path = &self.path;
+        match self.field {
+            Some(_) if self.fields.len() != 1 => None,
+            Some((Member::Unnamed(_), _)) => Some(quote!(#path(#expr))),
+            Some((Member::Named(field), _)) => Some(quote!(#path { #field: #expr })),
+            None => None,
+        }
+    }
+}
+
+/// The default name of the attribute that marks the field to delegate to.
+const DELEGATE: &str = "delegate";
+
+/// Returns the field to delegate to, or `None` if there are multiple fields
+/// and none of them is marked with `#[delegate]`.
+fn delegate_field<'a>(fields: &'a Fields, attr_name: &str) -> Result<Option<(Member, &'a Type)>> {
+    let mut marked = None;
+    for (i, f) in fields.iter().enumerate() {
+        for attr in &f.attrs {
+            if attr.path().is_ident(attr_name) {
+                attr.meta.require_path_only()?;
+                if marked.is_some() {
+                    bail!(attr, "duplicate #[{}] attribute", attr_name);
                 }
+                marked = Some((i, f));
+            }
+        }
+    }
+    let (i, f) = match marked {
+        Some(marked) => marked,
+        None if fields.len() == 1 => (0, fields.iter().next().unwrap()),
+        None => return Ok(None),
+    };
+    let member = match &f.ident {
+        Some(ident) => Member::Named(ident.clone()),
+        None => Member::Unnamed(Index::from(i)),
+    };
+    Ok(Some((member, &f.ty)))
+}
 
-                match &v.fields {
-                    Fields::Unnamed(f) => {
-                        field_types.push(f.unnamed.iter().next().unwrap().ty.clone());
-                        Ok(field_types)
-                    }
-                    Fields::Named(_) => {
-                        bail!(v, "may not be used on enums with variants with named fields");
+/// The default name of the attribute that marks variants to skip.
+const ENUM_DERIVE: &str = "enum_derive";
+
+/// Returns the fallback of a variant marked with `#[enum_derive(skip)]`, or
+/// `None` if the variant is not marked.
+fn skip_fallback(attrs: &[Attribute], attr_name: &str) -> Result<Option<Fallback>> {
+    let mut skip = None;
+    for attr in attrs {
+        if !attr.path().is_ident(attr_name) {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("skip") {
+                return Err(meta.error(format!("unknown #[{attr_name}] option")));
+            }
+            if skip.is_some() {
+                return Err(meta.error("duplicate `skip` option"));
+            }
+            let mut fallback = None;
+            if meta.input.peek(token::Paren) {
+                meta.parse_nested_meta(|meta| {
+                    if fallback.is_some() {
+                        return Err(meta.error("multiple fallbacks for `skip`"));
                     }
-                    Fields::Unit => unreachable!(),
-                }
-            },
-        )?;
+                    fallback = Some(if meta.path.is_ident("unreachable") {
+                        Fallback::Unreachable
+                    } else if meta.path.is_ident("default") {
+                        Fallback::Default
+                    } else if meta.path.is_ident("panic") {
+                        Fallback::Panic(meta.value()?.parse::<LitStr>()?.value())
+                    } else if meta.path.is_ident("with") {
+                        Fallback::Expr(meta.value()?.parse()?)
+                    } else {
+                        return Err(meta.error(
+                            "expected `unreachable`, `panic = \"...\"`, `default`, or `with = ...`",
+                        ));
+                    });
+                    Ok(())
+                })?;
+            }
+            skip = Some(fallback.unwrap_or(Fallback::Unreachable));
+            Ok(())
+        })?;
+    }
+    Ok(skip)
+}
 
-        Ok(Self { repr: item, field_types })
+/// Returns the `#[cfg]` attributes of a variant, including those in
+/// `#[cfg_attr]`.
+fn variant_cfg_attrs(attrs: &[Attribute]) -> Result<Vec<Attribute>> {
+    fn push(
+        attr: &Attribute,
+        meta: &Meta,
+        predicates: &[&Meta],
+        cfg_attrs: &mut Vec<Attribute>,
+    ) -> Result<()> {
+        if meta.path().is_ident("cfg") {
+            let inner = &meta.require_list()?.tokens;
+            // The variant is removed only if the predicates of `#[cfg_attr]` hold
+            // and the condition of `#[cfg]` does not.
+            cfg_attrs.push(parse_quote!(#[cfg(any(not(all(#(#predicates),*)), #inner))]));
+        } else if meta.path().is_ident("cfg_attr") {
+            let args = meta
+                .require_list()
+                .and_then(|list| {
+                    list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                })
+                .map_err(|e| format_err!(attr, "cfg_attr on variants is not supported: {}", e))?;
+            let mut args = args.iter();
+            let Some(predicate) = args.next() else {
+                bail!(attr, "cfg_attr on variants is not supported without a predicate");
+            };
+            let mut predicates = predicates.to_vec();
+            predicates.push(predicate);
+            for meta in args {
+                push(attr, meta, &predicates, cfg_attrs)?;
+            }
+        }
+        Ok(())
     }
+
+    let mut cfg_attrs = vec![];
+    for attr in attrs {
+        if attr.path().is_ident("cfg") {
+            cfg_attrs.push(attr.clone());
+        } else if attr.path().is_ident("cfg_attr") {
+            push(attr, &attr.meta, &[], &mut cfg_attrs)?;
+        }
+    }
+    Ok(cfg_attrs)
 }
 
-impl ToTokens for EnumData {
+/// Options for [`EnumData::parse_with`].
+#[derive(Clone, Default)]
+pub struct EnumDataOptions {
+    unit_variants: Option<Fallback>,
+    discriminants: bool,
+    empty: bool,
+    delegate_attr: Option<String>,
+    skip_attr: Option<String>,
+}
+
+impl EnumDataOptions {
+    /// Creates a new `EnumDataOptions` with the default options.
+    ///
+    /// By default, enums without variants, variants with zero fields, and
+    /// variants with explicit discriminants are rejected, the field to
+    /// delegate to is marked with `#[delegate]`, and variants to skip are
+    /// marked with `#[enum_derive(skip)]`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allows variants with zero fields.
+    ///
+    /// Methods generated by [`EnumImpl`](crate::EnumImpl) evaluate `fallback`
+    /// for these variants instead of delegating.
+    ///
+    /// ```text
+    /// match self {
+    ///     Enum::A(x) => x.next(),
+    ///     Enum::B { .. } => unreachable!(),
+    /// }
+    /// ```
+    #[must_use]
+    pub fn unit_variants(mut self, fallback: Fallback) -> Self {
+        self.unit_variants = Some(fallback);
+        self
+    }
+
+    /// Allows variants with explicit discriminants.
+    ///
+    /// Discriminants do not affect delegation, so this is mainly useful for
+    /// `#[repr(inttype)]` enums whose discriminants are part of their ABI.
+    ///
+    /// ```text
+    /// #[repr(u8)]
+    /// enum Enum<A, B> {
+    ///     A(A) = 1,
+    ///     B(B) = 2,
+    /// }
+    /// ```
+    #[must_use]
+    pub fn discriminants(mut self, allow: bool) -> Self {
+        self.discriminants = allow;
+        self
+    }
+
+    /// Allows enums that have no variants to delegate to, i.e., enums without
+    /// variants, or enums whose variants all have zero fields.
+    ///
+    /// Methods generated for an enum without variants match on an
+    /// uninhabited value, and methods generated for an enum whose variants all
+    /// have zero fields only evaluate the fallback. In both cases, associated
+    /// types cannot be delegated, so [`EnumImpl::from_trait`] panics if the
+    /// trait has associated types.
+    ///
+    /// [`EnumImpl::from_trait`]: crate::EnumImpl::from_trait
+    #[must_use]
+    pub fn empty(mut self, allow: bool) -> Self {
+        self.empty = allow;
+        self
+    }
+
+    /// Sets the name of the attribute that marks the field to delegate to.
+    ///
+    /// The default is `delegate`.
+    ///
+    /// ```text
+    /// // EnumDataOptions::new().delegate_attr("inner")
+    /// enum Enum<A, B> {
+    ///     A(#[inner] A, Span),
+    ///     B(B),
+    /// }
+    /// ```
+    #[must_use]
+    pub fn delegate_attr(mut self, name: &str) -> Self {
+        self.delegate_attr = Some(name.into());
+        self
+    }
+
+    /// Sets the name of the attribute that marks variants to skip.
+    ///
+    /// The default is `enum_derive`.
+    ///
+    /// ```text
+    /// // EnumDataOptions::new().skip_attr("iter")
+    /// enum Enum<A, B> {
+    ///     A(A),
+    ///     #[iter(skip(default))]
+    ///     B(B),
+    /// }
+    /// ```
+    #[must_use]
+    pub fn skip_attr(mut self, name: &str) -> Self {
+        self.skip_attr = Some(name.into());
+        self
+    }
+
+    fn delegate_attr_name(&self) -> &str {
+        self.delegate_attr.as_deref().unwrap_or(DELEGATE)
+    }
+
+    fn skip_attr_name(&self) -> &str {
+        self.skip_attr.as_deref().unwrap_or(ENUM_DERIVE)
+    }
+}
+
+/// An expression evaluated in place of delegating to a variant's field.
+#[derive(Clone)]
+#[non_exhaustive]
+pub enum Fallback {
+    /// `unreachable!()`
+    Unreachable,
+    /// `panic!("{}", message)`
+    Panic(String),
+    /// `Default::default()`
+    Default,
+    /// An arbitrary expression.
+    Expr(Expr),
+}
+
+impl ToTokens for Fallback {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        self.repr.to_tokens(tokens);
+        match self {
+            Self::Unreachable => tokens.extend(quote!(::core::unreachable!())),
+            Self::Panic(msg) => tokens.extend(quote!(::core::panic!("{}", #msg))),
+            Self::Default => tokens.extend(quote!(::core::default::Default::default())),
+            Self::Expr(expr) => expr.to_tokens(tokens),
+        }
     }
 }
@@ -0,0 +1,234 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Utilities for analyzing which generic parameters types use.
+//!
+//! This is useful for generating bounds only for types that depend on the
+//! generic parameters of an item. Adding bounds for types that do not depend
+//! on them can cause errors such as [overflow evaluating the requirement][47].
+//!
+//! [47]: https://github.com/taiki-e/derive_utils/issues/47
+
+use alloc::vec::Vec;
+
+use proc_macro2::{TokenStream, TokenTree};
+use syn::{
+    BoundLifetimes, ExprPath, GenericParam, Generics, Ident, Lifetime, Macro, Path, QSelf,
+    TraitBound, Type, TypeBareFn, TypePath,
+    visit::{self, Visit},
+};
+
+/// The generic parameters of an item.
+///
+/// # Examples
+///
+/// ```
+/// use derive_utils::generics::GenericParams;
+/// use syn::parse_quote;
+///
+/// let params = GenericParams::new(&parse_quote!(<'a, T, const N: usize>));
+/// assert!(params.is_used_by(&parse_quote!(&'a str)));
+/// assert!(params.is_used_by(&parse_quote!(<T as Iterator>::Item)));
+/// assert!(params.is_used_by(&parse_quote!([u8; N])));
+/// assert!(!params.is_used_by(&parse_quote!(::T)));
+/// assert!(!params.is_used_by(&parse_quote!(for<'a> fn(&'a str))));
+///
+/// let used = params.used_by(&parse_quote!(Option<&'a T>));
+/// assert_eq!(used.type_params().len(), 1);
+/// assert_eq!(used.lifetimes().len(), 1);
+/// assert!(used.const_params().is_empty());
+/// ```
+#[derive(Clone)]
+pub struct GenericParams {
+    type_params: Vec<Ident>,
+    lifetimes: Vec<Lifetime>,
+    const_params: Vec<Ident>,
+}
+
+impl GenericParams {
+    /// Collects the generic parameters declared in `generics`.
+    pub fn new(generics: &Generics) -> Self {
+        let mut type_params = Vec::new();
+        let mut lifetimes = Vec::new();
+        let mut const_params = Vec::new();
+        for param in &generics.params {
+            match param {
+                GenericParam::Type(param) => type_params.push(param.ident.clone()),
+                GenericParam::Lifetime(param) => lifetimes.push(param.lifetime.clone()),
+                GenericParam::Const(param) => const_params.push(param.ident.clone()),
+            }
+        }
+        Self { type_params, lifetimes, const_params }
+    }
+
+    /// Returns `true` if there are no generic parameters.
+    pub fn is_empty(&self) -> bool {
+        self.type_params.is_empty() && self.lifetimes.is_empty() && self.const_params.is_empty()
+    }
+
+    /// Returns `true` if `ty` uses any of the generic parameters.
+    pub fn is_used_by(&self, ty: &Type) -> bool {
+        !self.used_by(ty).is_empty()
+    }
+
+    /// Returns the generic parameters that `ty` uses.
+    ///
+    /// Lifetimes bound by higher-ranked trait bounds (`for<'a>`) shadow
+    /// lifetime parameters of the same name. The paths of qualified paths
+    /// (`<T as Trait>::Assoc`) and paths starting with `::` are not treated
+    /// as generic parameters. Types in macro invocations are conservatively
+    /// treated as using every parameter whose name appears in the tokens.
+    pub fn used_by(&self, ty: &Type) -> UsedParams {
+        let used =
+            UsedParams { type_params: Vec::new(), lifetimes: Vec::new(), const_params: Vec::new() };
+        let mut visitor = Visitor { params: self, bound_lifetimes: Vec::new(), used };
+        visitor.visit_type(ty);
+        visitor.used
+    }
+}
+
+/// The generic parameters used by a type.
+///
+/// This is returned by [`GenericParams::used_by`].
+#[derive(Clone)]
+pub struct UsedParams {
+    type_params: Vec<Ident>,
+    lifetimes: Vec<Lifetime>,
+    const_params: Vec<Ident>,
+}
+
+impl UsedParams {
+    /// Returns `true` if no generic parameters are used.
+    pub fn is_empty(&self) -> bool {
+        self.type_params.is_empty() && self.lifetimes.is_empty() && self.const_params.is_empty()
+    }
+
+    /// Returns the used type parameters, in order of first use.
+    pub fn type_params(&self) -> &[Ident] {
+        &self.type_params
+    }
+
+    /// Returns the used lifetime parameters, in order of first use.
+    pub fn lifetimes(&self) -> &[Lifetime] {
+        &self.lifetimes
+    }
+
+    /// Returns the used const parameters, in order of first use.
+    pub fn const_params(&self) -> &[Ident] {
+        &self.const_params
+    }
+}
+
+struct Visitor<'a> {
+    params: &'a GenericParams,
+    /// Lifetimes bound by enclosing higher-ranked trait bounds.
+    bound_lifetimes: Vec<Lifetime>,
+    used: UsedParams,
+}
+
+impl Visitor<'_> {
+    /// Records `ident` if it is a type or const parameter.
+    fn use_ident(&mut self, ident: &Ident) {
+        if self.params.type_params.contains(ident) {
+            if !self.used.type_params.contains(ident) {
+                self.used.type_params.push(ident.clone());
+            }
+        } else if self.params.const_params.contains(ident)
+            && !self.used.const_params.contains(ident)
+        {
+            self.used.const_params.push(ident.clone());
+        }
+    }
+
+    /// Records `lifetime` if it is a lifetime parameter that is not shadowed.
+    fn use_lifetime(&mut self, lifetime: &Lifetime) {
+        if self.params.lifetimes.contains(lifetime)
+            && !self.bound_lifetimes.contains(lifetime)
+            && !self.used.lifetimes.contains(lifetime)
+        {
+            self.used.lifetimes.push(lifetime.clone());
+        }
+    }
+
+    /// Records the first segment of `path` if it can be a generic parameter.
+    fn use_path(&mut self, qself: Option<&QSelf>, path: &Path) {
+        if qself.is_none() && path.leading_colon.is_none() {
+            if let Some(first) = path.segments.first() {
+                self.use_ident(&first.ident);
+            }
+        }
+    }
+
+    /// Records every generic parameter whose name appears in `tokens`.
+    fn visit_token_stream(&mut self, tokens: TokenStream) {
+        let mut tokens = tokens.into_iter().peekable();
+        while let Some(tt) = tokens.next() {
+            match tt {
+                TokenTree::Ident(ident) => self.use_ident(&ident),
+                TokenTree::Group(group) => self.visit_token_stream(group.stream()),
+                TokenTree::Punct(punct) if punct.as_char() == '\'' => {
+                    if let Some(TokenTree::Ident(ident)) = tokens.peek() {
+                        let lifetime = Lifetime { apostrophe: punct.span(), ident: ident.clone() };
+                        self.use_lifetime(&lifetime);
+                        tokens.next();
+                    }
+                }
+                TokenTree::Punct(_) | TokenTree::Literal(_) => {}
+            }
+        }
+    }
+
+    fn with_bound_lifetimes<F>(&mut self, bound: Option<&BoundLifetimes>, f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let len = self.bound_lifetimes.len();
+        if let Some(bound) = bound {
+            self.bound_lifetimes.extend(bound.lifetimes.iter().filter_map(|param| match param {
+                GenericParam::Lifetime(param) => Some(param.lifetime.clone()),
+                _ => None,
+            }));
+        }
+        f(self);
+        self.bound_lifetimes.truncate(len);
+    }
+}
+
+impl<'ast> Visit<'ast> for Visitor<'_> {
+    fn visit_type_path(&mut self, node: &'ast TypePath) {
+        self.use_path(node.qself.as_ref(), &node.path);
+        visit::visit_type_path(self, node);
+    }
+
+    fn visit_expr_path(&mut self, node: &'ast ExprPath) {
+        self.use_path(node.qself.as_ref(), &node.path);
+        visit::visit_expr_path(self, node);
+    }
+
+    fn visit_lifetime(&mut self, lifetime: &'ast Lifetime) {
+        self.use_lifetime(lifetime);
+    }
+
+    fn visit_type_bare_fn(&mut self, node: &'ast TypeBareFn) {
+        self.with_bound_lifetimes(node.lifetimes.as_ref(), |this| {
+            visit::visit_type_bare_fn(this, node);
+        });
+    }
+
+    fn visit_trait_bound(&mut self, node: &'ast TraitBound) {
+        self.with_bound_lifetimes(node.lifetimes.as_ref(), |this| {
+            visit::visit_trait_bound(this, node);
+        });
+    }
+
+    fn visit_macro(&mut self, node: &'ast Macro) {
+        self.visit_token_stream(node.tokens.clone());
+    }
+
+    fn visit_type(&mut self, node: &'ast Type) {
+        if let Type::Verbatim(tokens) = node {
+            self.visit_token_stream(tokens.clone());
+        } else {
+            visit::visit_type(self, node);
+        }
+    }
+}
@@ -152,22 +152,29 @@ extern crate std;
 mod error;
 
 mod ast;
+pub mod generics;
 mod parse;
+mod ty;
 
 pub use self::{
-    ast::EnumData,
-    parse::{EnumImpl, derive_trait},
+    ast::{EnumData, EnumDataBuilder, EnumDataOptions, Fallback, StructData, VariantData},
+    parse::{
+        EnumImpl, EnumImplOptions, MismatchedVariants, ProvidedMethods, derive_struct_trait,
+        derive_struct_trait_with, derive_trait, derive_trait_with,
+    },
 };
 
 /// A macro for making easy to write `proc_macro_derive` like deriving trait to
 /// enum so long as all variants are implemented that trait.
 ///
+/// Newtype structs (see [`StructData`]) are also accepted.
+///
 /// See the [crate-level documentation](crate) for details.
 #[macro_export]
 macro_rules! quick_derive {
     ($input:expr, $trait_path:expr, <$super:ident>, $($trait_def:tt)*) => {
         $crate::__private::parse_input($input, |data| {
-            $crate::derive_trait(
+            $crate::__private::derive_trait(
                 &data,
                 &$crate::__private::parse_quote!($trait_path),
                 $crate::__private::Some(
@@ -180,7 +187,7 @@ macro_rules! quick_derive {
     };
     ($input:expr, $trait_path:expr, <$($super:ident),+ $(,)?>, $($trait_def:tt)*) => {
         $crate::__private::parse_input($input, |data| {
-            $crate::derive_trait(
+            $crate::__private::derive_trait(
                 &data,
                 &$crate::__private::parse_quote!($trait_path),
                 $crate::__private::vec![
@@ -193,7 +200,7 @@ macro_rules! quick_derive {
     };
     ($input:expr, $trait_path:expr, $($trait_def:tt)*) => {
         $crate::__private::parse_input($input, |data| {
-            $crate::derive_trait(
+            $crate::__private::derive_trait(
                 &data,
                 &$crate::__private::parse_quote!($trait_path),
                 $crate::__private::None,
@@ -218,17 +225,61 @@ pub mod __private {
     use proc_macro2::TokenStream;
     #[doc(hidden)]
     pub use quote::{format_ident, quote};
-    use syn::Error;
+    use syn::{
+        Attribute, Error, Ident, Token, Visibility,
+        parse::{Parse, ParseStream},
+    };
     #[doc(hidden)]
     pub use syn::{ItemTrait, Path, parse_quote, parse2};
 
-    use crate::EnumData;
+    use crate::{EnumData, StructData};
+
+    #[doc(hidden)]
+    #[allow(clippy::exhaustive_enums)]
+    pub enum Input {
+        Enum(EnumData),
+        Struct(StructData),
+    }
+
+    impl Parse for Input {
+        fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+            let fork = input.fork();
+            fork.call(Attribute::parse_outer)?;
+            fork.parse::<Visibility>()?;
+            if fork.peek(Token![struct]) {
+                input.parse().map(Self::Struct)
+            } else {
+                input.parse().map(Self::Enum)
+            }
+        }
+    }
 
     #[doc(hidden)]
-    pub fn parse_input<T: Into<TokenStream>, F: Fn(EnumData) -> TokenStream>(
+    pub fn parse_input<T: Into<TokenStream>, F: Fn(Input) -> TokenStream>(
         input: T,
         f: F,
     ) -> TokenStream {
-        parse2::<EnumData>(input.into()).map_or_else(Error::into_compile_error, f)
+        parse2::<Input>(input.into()).map_or_else(Error::into_compile_error, f)
+    }
+
+    #[doc(hidden)]
+    pub fn derive_trait<I>(
+        data: &Input,
+        trait_path: &Path,
+        supertraits_types: I,
+        trait_def: ItemTrait,
+    ) -> TokenStream
+    where
+        I: IntoIterator<Item = Ident>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        match data {
+            Input::Enum(data) => {
+                crate::derive_trait(data, trait_path, supertraits_types, trait_def)
+            }
+            Input::Struct(data) => {
+                crate::derive_struct_trait(data, trait_path, supertraits_types, trait_def)
+            }
+        }
     }
 }
@@ -3,21 +3,37 @@
 use alloc::{
     borrow::Cow,
     boxed::Box,
+    collections::BTreeSet,
+    format,
     string::{String, ToString as _},
     vec,
     vec::Vec,
 };
-use core::mem;
+use core::{mem, ptr};
 
-use proc_macro2::{TokenStream, TokenTree};
-use quote::{ToTokens as _, quote};
+use proc_macro2::{Span, TokenStream};
+use quote::{ToTokens as _, format_ident, quote};
 use syn::{
-    Block, FnArg, GenericParam, Generics, Ident, ImplItem, ImplItemFn, ItemImpl, ItemTrait, Path,
-    Signature, Stmt, Token, TraitItem, TraitItemFn, TraitItemType, Type, TypeParamBound, TypePath,
-    Visibility, WherePredicate, parse_quote, token,
+    Attribute, Block, ConstParam, Expr, FnArg, GenericArgument, GenericParam, Generics, Ident,
+    ImplItem, ImplItemConst, ImplItemFn, ImplItemType, ItemImpl, ItemTrait, Lifetime, Macro, Meta,
+    Path, PathArguments, Receiver, ReturnType, Signature, Stmt, Token, TraitItem, TraitItemConst,
+    TraitItemFn, TraitItemType, Type, TypeParam, TypeParamBound, TypePath, TypeReference,
+    Visibility, WherePredicate, parse_quote,
+    punctuated::Punctuated,
+    token,
+    visit::{self, Visit},
+    visit_mut::{self, VisitMut},
 };
 
-use crate::ast::EnumData;
+use crate::{
+    ast::{self, Arm, Data, EnumData, Fallback, StructData},
+    generics::GenericParams,
+    ty::{self, TypeKey},
+};
+
+/// The maximum number of distinct `#[cfg]` conditions for which
+/// [`EnumImpl::build`] emits one impl per combination.
+const MAX_CFG_CONDITIONS: usize = 4;
 
 /// A function for creating `proc_macro_derive` like deriving trait to enum so
 /// long as all variants are implemented that trait.
@@ -88,60 +104,414 @@ where
     EnumImpl::from_trait(data, trait_path, supertraits_types, trait_def).build()
 }
 
+/// A function for creating `proc_macro_derive` like deriving trait to enum
+/// with the given options.
+///
+/// This is the same as [`derive_trait`], except that it takes an
+/// [`EnumImplOptions`].
+pub fn derive_trait_with<I>(
+    data: &EnumData,
+    trait_path: &Path,
+    supertraits_types: I,
+    trait_def: ItemTrait,
+    options: EnumImplOptions,
+) -> TokenStream
+where
+    I: IntoIterator<Item = Ident>,
+    I::IntoIter: ExactSizeIterator,
+{
+    EnumImpl::from_trait_with(data, trait_path, supertraits_types, trait_def, options).build()
+}
+
+/// A function for creating `proc_macro_derive` like deriving trait to newtype
+/// struct so long as the field to delegate to is implemented that trait.
+///
+/// This is the same as [`derive_trait`], except that it takes a [`StructData`].
+pub fn derive_struct_trait<I>(
+    data: &StructData,
+    trait_path: &Path,
+    supertraits_types: I,
+    trait_def: ItemTrait,
+) -> TokenStream
+where
+    I: IntoIterator<Item = Ident>,
+    I::IntoIter: ExactSizeIterator,
+{
+    EnumImpl::from_struct_trait(data, trait_path, supertraits_types, trait_def).build()
+}
+
+/// A function for creating `proc_macro_derive` like deriving trait to newtype
+/// struct with the given options.
+///
+/// This is the same as [`derive_struct_trait`], except that it takes an
+/// [`EnumImplOptions`].
+pub fn derive_struct_trait_with<I>(
+    data: &StructData,
+    trait_path: &Path,
+    supertraits_types: I,
+    trait_def: ItemTrait,
+    options: EnumImplOptions,
+) -> TokenStream
+where
+    I: IntoIterator<Item = Ident>,
+    I::IntoIter: ExactSizeIterator,
+{
+    EnumImpl::from_struct_trait_with(data, trait_path, supertraits_types, trait_def, options)
+        .build()
+}
+
+/// Options for [`EnumImpl::from_trait_with`].
+#[derive(Clone, Default)]
+pub struct EnumImplOptions {
+    provided_methods: ProvidedMethods,
+    check_assoc_consts: bool,
+    assoc_const_values: Vec<(String, Expr)>,
+    mismatched_variants: Vec<(String, MismatchedVariants)>,
+    skip_unpin_impl: bool,
+    assoc_type_enums: Vec<String>,
+    assoc_type_targets: Vec<(String, Type)>,
+}
+
+impl EnumImplOptions {
+    /// Creates a new `EnumImplOptions` with the default options.
+    ///
+    /// By default, provided methods are skipped.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how to handle methods that have a default body in the trait
+    /// definition.
+    #[must_use]
+    pub fn provided_methods(mut self, mode: ProvidedMethods) -> Self {
+        self.provided_methods = mode;
+        self
+    }
+
+    /// Checks that associated constants of all variants have the same value.
+    ///
+    /// Associated constants are delegated to the first variant. If this is
+    /// enabled, evaluating a constant also asserts that the constants of the
+    /// other variants are equal to it. Since constants of an impl are evaluated
+    /// only when used, the check fails to compile only if the constant is
+    /// used.
+    ///
+    /// The check is supported for constants of integer types, `bool`, `char`,
+    /// and `&str`. Constants of other types are not checked.
+    ///
+    /// ```text
+    /// const NAME: &'static str = {
+    ///     assert!(/* <B as Trait>::NAME == <A as Trait>::NAME */);
+    ///     <A as Trait>::NAME
+    /// };
+    /// ```
+    #[must_use]
+    pub fn check_assoc_consts(mut self, check: bool) -> Self {
+        self.check_assoc_consts = check;
+        self
+    }
+
+    /// Sets the value of the associated constant `name` instead of delegating
+    /// it to the first variant.
+    ///
+    /// This is also needed for enums that have no variants to delegate to.
+    #[must_use]
+    pub fn assoc_const_value(mut self, name: &str, value: Expr) -> Self {
+        self.assoc_const_values.push((name.into(), value));
+        self
+    }
+
+    /// Sets how the method `name` handles arguments of type `Self` that are
+    /// different variants from the receiver.
+    ///
+    /// By default, such methods panic.
+    #[must_use]
+    pub fn mismatched_variants(mut self, name: &str, mismatch: MismatchedVariants) -> Self {
+        self.mismatched_variants.push((name.into(), mismatch));
+        self
+    }
+
+    /// Sets whether to emit the `Unpin` impl for methods with `Pin` receivers.
+    ///
+    /// See [`EnumImpl::build`] for details. Since the `Unpin` impls of
+    /// multiple impls for the same enum conflict, derive macros that are used
+    /// together for the same enum (e.g., `Future` and `Stream`) can disable
+    /// this for all but one of them.
+    ///
+    /// If this is disabled, the generated code checks that another impl for
+    /// the same enum in the same module emits the `Unpin` impl, and fails to
+    /// compile otherwise, since the projections are unsound without it. The
+    /// error mentions `__unpin_impl_false_requires_another_impl_to_emit_Unpin_for_{Enum}`.
+    ///
+    /// The default is `true`.
+    #[must_use]
+    pub fn unpin_impl(mut self, emit: bool) -> Self {
+        self.skip_unpin_impl = !emit;
+        self
+    }
+
+    /// Generates an enum for the associated type `name`, instead of requiring
+    /// the associated types of all variants to be the same.
+    ///
+    /// The generated enum is named after the enum and the associated type
+    /// (e.g., `EitherIntoIter` for `IntoIter` of `Either`), has the visibility
+    /// of the enum, and has one variant for each variant to delegate to. It
+    /// implements the traits in the bounds of the associated type; the
+    /// supported traits are the same as those of methods returning
+    /// `impl Trait` (see [`EnumImpl::push_method`]). Methods that return
+    /// `Self::name` wrap the results of the variants in it.
+    ///
+    /// This is not supported for generic associated types.
+    #[must_use]
+    pub fn assoc_type_enum(mut self, name: &str) -> Self {
+        self.assoc_type_enums.push(name.to_string());
+        self
+    }
+
+    /// Sets the associated type `name` to `target`, instead of requiring the
+    /// associated types of all variants to be the same.
+    ///
+    /// The associated types of the variants are required to implement
+    /// `Into<target>`. Methods that return `Self::name` or
+    /// `Result<_, Self::name>` convert the results of the variants with it.
+    /// This is useful for error types (e.g., `Error = MyError`).
+    ///
+    /// This is not supported for generic associated types.
+    #[must_use]
+    pub fn assoc_type_target(mut self, name: &str, target: Type) -> Self {
+        self.assoc_type_targets.push((name.to_string(), target));
+        self
+    }
+
+    /// Returns `true` if the associated type `ident` is not delegated to the
+    /// first variant.
+    fn is_custom_assoc_type(&self, ident: &Ident) -> bool {
+        self.assoc_type_enums.iter().any(|name| ident == name)
+            || self.assoc_type_targets.iter().any(|(name, _)| ident == name)
+    }
+}
+
+/// How to handle methods that have a default body in the trait definition.
+#[derive(Clone, Copy, Default)]
+#[non_exhaustive]
+pub enum ProvidedMethods {
+    /// Does not implement provided methods, so the default bodies apply.
+    #[default]
+    Skip,
+    /// Implements provided methods by delegating to each variant, like
+    /// required methods.
+    ///
+    /// This is useful for methods that variants may override for performance,
+    /// such as `Iterator::fold` and `Iterator::nth`. Methods that cannot be
+    /// delegated are skipped, i.e., methods that mention `Self` other than in
+    /// the receiver, the return type (see [`EnumImpl::push_method`]),
+    /// `Self::Assoc` paths, or `Self` bounds in the `where`-clause, and methods
+    /// with no receiver or an unsupported receiver.
+    Forward,
+}
+
+/// How methods that take arguments of type `Self` handle arguments that are
+/// different variants from the receiver.
+#[derive(Clone)]
+#[non_exhaustive]
+pub enum MismatchedVariants {
+    /// Evaluates the fallback, e.g., `false` for `PartialEq::eq`.
+    Fallback(Fallback),
+    /// Compares the indices of the variants of the receiver and the first
+    /// argument of type `Self`, for methods that return `Ordering` or
+    /// `Option<Ordering>`, e.g., `Ord::cmp` and `PartialOrd::partial_cmp`.
+    CompareIndices,
+}
+
 /// A builder for implementing a trait for enums.
+///
+/// Newtype structs are also supported via [`EnumImpl::new_struct`] and
+/// [`EnumImpl::from_struct_trait`].
 pub struct EnumImpl<'a> {
-    data: &'a EnumData,
+    data: Data<'a>,
     defaultness: bool,
     unsafety: bool,
     generics: Generics,
     trait_: Option<Path>,
     self_ty: Box<Type>,
     items: Vec<ImplItem>,
+    options: EnumImplOptions,
+    /// Predicates that only hold when the `#[cfg]` attributes are active.
+    cfg_predicates: Vec<(Vec<Attribute>, WherePredicate)>,
+    /// Whether a method projects a `Pin` receiver to the fields.
+    pinned: bool,
+    /// Items emitted next to the impl, such as the enums generated for
+    /// associated types.
+    extra_items: TokenStream,
+    /// Associated types whose values are generated enums with arguments for
+    /// variants gated by `#[cfg]`: the associated type, the enum, and the
+    /// arguments with the `#[cfg]` attributes of their variants.
+    gated_assoc_types: Vec<(Ident, Ident, Vec<(Type, Vec<Attribute>)>)>,
 }
 
 impl<'a> EnumImpl<'a> {
     /// Creates a new `EnumImpl`.
     pub fn new(data: &'a EnumData) -> Self {
-        let ident = &data.ident;
-        let ty_generics = data.generics.split_for_impl().1;
+        Self::from_data(Data::Enum(data))
+    }
+
+    /// Creates a new `EnumImpl` for a newtype struct.
+    pub fn new_struct(data: &'a StructData) -> Self {
+        Self::from_data(Data::Struct(data))
+    }
+
+    fn from_data(data: Data<'a>) -> Self {
         Self {
             data,
             defaultness: false,
             unsafety: false,
-            generics: data.generics.clone(),
+            generics: data.generics().clone(),
             trait_: None,
-            self_ty: Box::new(parse_quote!(#ident #ty_generics)),
+            self_ty: Box::new(ast::self_ty(data.ident(), data.generics())),
             items: vec![],
+            options: EnumImplOptions::new(),
+            cfg_predicates: vec![],
+            pinned: false,
+            extra_items: TokenStream::new(),
+            gated_assoc_types: vec![],
         }
     }
 
     /// Creates a new `EnumImpl` from a trait definition.
     ///
+    /// Associated constants are delegated to the first variant, unless their
+    /// values are given by [`EnumImplOptions::assoc_const_value`].
+    ///
+    /// Methods that return `Self` wrap the results of variants in the same
+    /// variant (see [`EnumImpl::push_method`]).
+    ///
+    /// Associated types are delegated to the first variant, and the other
+    /// variants are required to have the same associated types, unless an enum
+    /// is generated for them by [`EnumImplOptions::assoc_type_enum`] or their
+    /// type is given by [`EnumImplOptions::assoc_type_target`].
+    ///
+    /// Generic associated types (GATs) are delegated with their generics and
+    /// `where`-clauses. The equality bounds between variants are only
+    /// generated for GATs that have only lifetime parameters, as higher-ranked
+    /// bounds (`for<'a>`). Otherwise, variants whose types use generic
+    /// parameters may fail to type-check. Since a higher-ranked bound cannot
+    /// be conditional on the `where`-clause of the GAT (e.g., `where Self: 'a`),
+    /// the types of the variants are required to be `'static` if the GAT has
+    /// one and some of the types use type or const parameters. If none of them
+    /// do (e.g., they only borrow data), no bounds are generated for them.
+    ///
     /// The following items are ignored:
-    /// - Generic associated types (GAT) ([`TraitItem::Type`] that has generics)
-    /// - [`TraitItem::Const`]
+    /// - Associated constants that have a default value or generics
     /// - [`TraitItem::Macro`]
     /// - [`TraitItem::Verbatim`]
+    /// - Methods that have a default body (use [`EnumImpl::from_trait_with`]
+    ///   to forward them)
     ///
     /// # Panics
     ///
-    /// Panics if a trait method has a body, no receiver, or a receiver other
+    /// Panics if a required trait method has no receiver, or a receiver other
     /// than the following:
     ///
     /// - `&self`
     /// - `&mut self`
     /// - `self`
+    /// - `self: Pin<&Self>`
+    /// - `self: Pin<&mut Self>`
+    /// - `self: Box<Self>`
+    ///
+    /// Methods with `self: Rc<Self>` or `self: Arc<Self>` receivers cannot be
+    /// delegated, so their bodies are `compile_error!` invocations.
+    ///
+    /// Also panics if the trait has associated types and the enum has no
+    /// variants to delegate to.
     pub fn from_trait<I>(
         data: &'a EnumData,
         trait_path: &Path,
         supertraits_types: I,
+        trait_def: ItemTrait,
+    ) -> Self
+    where
+        I: IntoIterator<Item = Ident>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        Self::from_trait_with(
+            data,
+            trait_path,
+            supertraits_types,
+            trait_def,
+            EnumImplOptions::new(),
+        )
+    }
+
+    /// Creates a new `EnumImpl` from a trait definition with the given options.
+    ///
+    /// See [`EnumImpl::from_trait`] for details.
+    pub fn from_trait_with<I>(
+        data: &'a EnumData,
+        trait_path: &Path,
+        supertraits_types: I,
+        trait_def: ItemTrait,
+        options: EnumImplOptions,
+    ) -> Self
+    where
+        I: IntoIterator<Item = Ident>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        Self::from_data_trait(Data::Enum(data), trait_path, supertraits_types, trait_def, options)
+    }
+
+    /// Creates a new `EnumImpl` for a newtype struct from a trait definition.
+    ///
+    /// See [`EnumImpl::from_trait`] for details.
+    pub fn from_struct_trait<I>(
+        data: &'a StructData,
+        trait_path: &Path,
+        supertraits_types: I,
+        trait_def: ItemTrait,
+    ) -> Self
+    where
+        I: IntoIterator<Item = Ident>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        Self::from_struct_trait_with(
+            data,
+            trait_path,
+            supertraits_types,
+            trait_def,
+            EnumImplOptions::new(),
+        )
+    }
+
+    /// Creates a new `EnumImpl` for a newtype struct from a trait definition
+    /// with the given options.
+    ///
+    /// See [`EnumImpl::from_trait`] for details.
+    pub fn from_struct_trait_with<I>(
+        data: &'a StructData,
+        trait_path: &Path,
+        supertraits_types: I,
+        trait_def: ItemTrait,
+        options: EnumImplOptions,
+    ) -> Self
+    where
+        I: IntoIterator<Item = Ident>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        Self::from_data_trait(Data::Struct(data), trait_path, supertraits_types, trait_def, options)
+    }
+
+    fn from_data_trait<I>(
+        data: Data<'a>,
+        trait_path: &Path,
+        supertraits_types: I,
         mut trait_def: ItemTrait,
+        options: EnumImplOptions,
     ) -> Self
     where
         I: IntoIterator<Item = Ident>,
         I::IntoIter: ExactSizeIterator,
     {
-        let mut generics = data.generics.clone();
+        let mut generics = data.generics().clone();
         let trait_ = {
             if trait_def.generics.params.is_empty() {
                 trait_path.clone()
@@ -151,12 +521,25 @@ impl<'a> EnumImpl<'a> {
             }
         };
 
-        let fst = data.field_types().next().unwrap();
+        let fields = data.fields();
+        let fst = data.first_field_type();
         let mut types: Vec<_> = trait_def
             .items
             .iter()
             .filter_map(|item| match item {
-                TraitItem::Type(ty) => Some((false, Cow::Borrowed(&ty.ident))),
+                TraitItem::Type(ty) if options.is_custom_assoc_type(&ty.ident) => None,
+                TraitItem::Type(ty) if ty.generics.params.is_empty() => {
+                    Some((false, Cow::Borrowed(&ty.ident), None))
+                }
+                TraitItem::Type(ty) => {
+                    // Rename parameters of the GAT that would shadow those of the impl
+                    // in higher-ranked bounds.
+                    let mut ty = ty.clone();
+                    if let Some(mut rename) = RenameShadowing::new(&ty.generics, &generics) {
+                        rename.visit_trait_item_type_mut(&mut ty);
+                    }
+                    Some((false, Cow::Owned(ty.ident), Some(ty.generics)))
+                }
                 _ => None,
             })
             .collect();
@@ -164,93 +547,166 @@ impl<'a> EnumImpl<'a> {
         let supertraits_types = supertraits_types.into_iter();
         if supertraits_types.len() > 0 {
             if let Some(TypeParamBound::Trait(_)) = trait_def.supertraits.iter().next() {
-                types.extend(supertraits_types.map(|ident| (true, Cow::Owned(ident))));
+                types.extend(supertraits_types.map(|ident| (true, Cow::Owned(ident), None)));
             }
         }
 
-        // https://github.com/taiki-e/derive_utils/issues/47
-        let type_params = generics.type_params().map(|p| p.ident.to_string()).collect::<Vec<_>>();
-        let has_method = trait_def.items.iter().any(|i| matches!(i, TraitItem::Fn(..)));
-        if !has_method || !type_params.is_empty() {
-            struct HasTypeParam<'a>(&'a [String]);
+        if fst.is_none() && !types.is_empty() {
+            panic!(
+                "associated types of `{}` cannot be delegated for enums without variants to delegate to",
+                trait_path.to_token_stream()
+            );
+        }
 
-            impl HasTypeParam<'_> {
-                fn check_ident(&self, ident: &Ident) -> bool {
-                    let ident = ident.to_string();
-                    self.0.contains(&ident)
-                }
+        // Predicates for variants gated by `#[cfg]` cannot be added directly to the
+        // where-clause. They are added to each impl emitted by `build` instead.
+        let mut predicates = vec![];
 
-                fn visit_type(&self, ty: &Type) -> bool {
-                    if let Type::Path(node) = ty {
-                        if node.qself.is_none() {
-                            if let Some(ident) = node.path.get_ident() {
-                                return self.check_ident(ident);
-                            }
-                        }
+        // Bounds that convert the associated types of a variant to the types
+        // given by `EnumImplOptions::assoc_type_target`.
+        let into_targets = |variant: &Type| -> Vec<WherePredicate> {
+            trait_def
+                .items
+                .iter()
+                .filter_map(|item| match item {
+                    TraitItem::Type(ty) => {
+                        options.assoc_type_targets.iter().find(|(name, _)| ty.ident == name)
                     }
-                    self.visit_token_stream(ty.to_token_stream())
-                }
+                    _ => None,
+                })
+                .map(|(name, target)| {
+                    let ident = format_ident!("{}", name);
+                    parse_quote!(<#variant as #trait_>::#ident: ::core::convert::Into<#target>)
+                })
+                .collect()
+        };
 
-                fn visit_token_stream(&self, tokens: TokenStream) -> bool {
-                    for tt in tokens {
-                        match tt {
-                            TokenTree::Ident(ident) => {
-                                if self.check_ident(&ident) {
-                                    return true;
-                                }
+        // https://github.com/taiki-e/derive_utils/issues/47
+        // Only field types that use generic parameters (including lifetime and
+        // const parameters) get bounds if the trait has methods. Field types
+        // that contain the enum itself (e.g., `Box<Self>`) get no bounds either,
+        // since they are implied by the impl and proving them would overflow.
+        //
+        // The higher-ranked equality bounds of GATs with `where`-clauses (e.g.,
+        // `where Self: 'a`) can only be proven for `'static` types. If no field
+        // type uses type or const parameters, no bounds are emitted at all,
+        // since the types are checked without them, and a bound on such a type
+        // would shadow its impl and prevent normalizing the GAT.
+        let params = GenericParams::new(&generics);
+        let has_method = trait_def.items.iter().any(|i| matches!(i, TraitItem::Fn(..)));
+        let self_ty = ast::self_ty(data.ident(), data.generics());
+        let has_bounded_gat = types.iter().any(|(_, _, gat)| {
+            gat.as_ref().is_some_and(|gat| {
+                gat.type_params().next().is_none()
+                    && gat.const_params().next().is_none()
+                    && gat.where_clause.as_ref().is_some_and(|w| !w.predicates.is_empty())
+            })
+        });
+        let is_generic = |ty: &Type| {
+            let used = params.used_by(ty);
+            !used.type_params().is_empty() || !used.const_params().is_empty()
+        };
+        let needs_static = has_bounded_gat && fields.iter().any(|(ty, _)| is_generic(ty));
+        let needs_bound = |ty: &Type| {
+            (!has_bounded_gat || needs_static)
+                && (!has_method || params.is_used_by(ty) && !ty::contains_self_type(ty, &self_ty))
+        };
+        if let Some(fst) = fst.filter(|_| !has_method || !params.is_empty()) {
+            let fst_index = fields.iter().position(|(ty, _)| ptr::eq(*ty, fst)).unwrap_or(0);
+            if needs_bound(fst) {
+                let cfg_attrs = &fields[fst_index].1;
+                predicates.push((cfg_attrs.clone(), parse_quote!(#fst: #trait_)));
+                predicates.extend(into_targets(fst).into_iter().map(|p| (cfg_attrs.clone(), p)));
+            }
+            if fields.len() > 1 {
+                // Types are deduplicated by their normalized form. A type of a
+                // variant gated by `#[cfg]` is a duplicate if the same type is
+                // not gated or is gated by the same attributes.
+                let keys: Vec<_> =
+                    fields.iter().map(|(ty, _)| TypeKey::new(ty, &self_ty)).collect();
+                let cfg_key = |cfg_attrs: &[&Attribute]| -> String {
+                    cfg_attrs.iter().map(|attr| attr.to_token_stream().to_string()).collect()
+                };
+                let mut seen = BTreeSet::new();
+                seen.insert((&keys[fst_index], cfg_key(&fields[fst_index].1)));
+                if needs_static {
+                    predicates.push((fields[fst_index].1.clone(), parse_quote!(#fst: 'static)));
+                }
+                let trait_args: Vec<_> = trait_def
+                    .generics
+                    .params
+                    .iter()
+                    .map(|param| match param {
+                        GenericParam::Lifetime(def) => def.lifetime.to_token_stream(),
+                        GenericParam::Type(param) => param.ident.to_token_stream(),
+                        GenericParam::Const(param) => param.ident.to_token_stream(),
+                    })
+                    .collect();
+                for (i, (variant, cfg_attrs)) in fields.iter().enumerate() {
+                    if i == fst_index {
+                        continue;
+                    }
+                    if !needs_bound(variant) {
+                        continue;
+                    }
+                    let key = &keys[i];
+                    if seen.contains(&(key, String::new())) {
+                        continue;
+                    }
+                    if !seen.insert((key, cfg_key(cfg_attrs))) {
+                        continue;
+                    }
+                    let mut push = |p: WherePredicate| predicates.push((cfg_attrs.clone(), p));
+                    let mut bindings = vec![];
+                    for (supertraits, ident, gat) in &types {
+                        let trait_ = match trait_def.supertraits.iter().next() {
+                            Some(TypeParamBound::Trait(trait_)) if *supertraits => {
+                                trait_.to_token_stream()
                             }
-                            TokenTree::Group(group) => {
-                                let content = group.stream();
-                                if self.visit_token_stream(content) {
-                                    return true;
-                                }
+                            _ => trait_.to_token_stream(),
+                        };
+                        match gat {
+                            None => bindings.push(quote!(#ident = <#fst as #trait_>::#ident)),
+                            // The equality bound of a GAT needs to hold for all of its
+                            // generic arguments, which can only be expressed for lifetimes.
+                            // A higher-ranked bound also cannot be conditional on the
+                            // `where`-clause of the GAT, hence `needs_static`.
+                            Some(gat)
+                                if gat.type_params().next().is_none()
+                                    && gat.const_params().next().is_none() =>
+                            {
+                                let lifetimes: Vec<_> =
+                                    gat.lifetimes().map(|p| &p.lifetime).collect();
+                                let args = quote!(<#(#lifetimes),*>);
+                                push(parse_quote! {
+                                    for<#(#lifetimes),*> #variant: #trait_path<
+                                        #(#trait_args,)*
+                                        #ident #args = <#fst as #trait_>::#ident #args
+                                    >
+                                });
                             }
-                            _ => {}
+                            Some(_) => {}
                         }
                     }
-                    false
+                    if bindings.is_empty() {
+                        push(parse_quote!(#variant: #trait_));
+                    } else {
+                        push(parse_quote!(#variant: #trait_path<#(#trait_args,)* #(#bindings),*>));
+                    }
+                    if needs_static {
+                        push(parse_quote!(#variant: 'static));
+                    }
+                    into_targets(variant).into_iter().for_each(push);
                 }
             }
+        }
 
-            let visitor = HasTypeParam(&type_params);
-            let where_clause = &mut generics.make_where_clause().predicates;
-            if !has_method || visitor.visit_type(fst) {
-                where_clause.push(parse_quote!(#fst: #trait_));
-            }
-            if data.field_types().len() > 1 {
-                let fst_tokens = fst.to_token_stream().to_string();
-                where_clause.extend(data.field_types().skip(1).filter_map(
-                    |variant| -> Option<WherePredicate> {
-                        if has_method && !visitor.visit_type(variant) {
-                            return None;
-                        }
-                        if variant.to_token_stream().to_string() == fst_tokens {
-                            return None;
-                        }
-                        if types.is_empty() {
-                            return Some(parse_quote!(#variant: #trait_));
-                        }
-                        let types = types.iter().map(|(supertraits, ident)| {
-                            match trait_def.supertraits.iter().next() {
-                                Some(TypeParamBound::Trait(trait_)) if *supertraits => {
-                                    quote!(#ident = <#fst as #trait_>::#ident)
-                                }
-                                _ => quote!(#ident = <#fst as #trait_>::#ident),
-                            }
-                        });
-                        if trait_def.generics.params.is_empty() {
-                            Some(parse_quote!(#variant: #trait_path<#(#types),*>))
-                        } else {
-                            let generics =
-                                trait_def.generics.params.iter().map(|param| match param {
-                                    GenericParam::Lifetime(def) => def.lifetime.to_token_stream(),
-                                    GenericParam::Type(param) => param.ident.to_token_stream(),
-                                    GenericParam::Const(param) => param.ident.to_token_stream(),
-                                });
-                            Some(parse_quote!(#variant: #trait_path<#(#generics),*, #(#types),*>))
-                        }
-                    },
-                ));
+        let mut cfg_predicates = vec![];
+        for (cfg_attrs, predicate) in predicates {
+            if cfg_attrs.is_empty() {
+                generics.make_where_clause().predicates.push(predicate);
+            } else {
+                cfg_predicates.push((cfg_attrs.into_iter().cloned().collect(), predicate));
             }
         }
 
@@ -264,16 +720,19 @@ impl<'a> EnumImpl<'a> {
             }
         }
 
-        let ident = &data.ident;
-        let ty_generics = data.generics.split_for_impl().1;
         let mut impls = Self {
             data,
             defaultness: false,
             unsafety: trait_def.unsafety.is_some(),
             generics,
             trait_: Some(trait_),
-            self_ty: Box::new(parse_quote!(#ident #ty_generics)),
+            self_ty: Box::new(ast::self_ty(data.ident(), data.generics())),
             items: Vec::with_capacity(trait_def.items.len()),
+            options,
+            cfg_predicates,
+            pinned: false,
+            extra_items: TokenStream::new(),
+            gated_assoc_types: vec![],
         };
         impls.append_items_from_trait(trait_def);
         impls
@@ -300,21 +759,134 @@ impl<'a> EnumImpl<'a> {
 
     /// Appends a method to impl items.
     ///
+    /// If the method has a default body, it is skipped or forwarded according
+    /// to the [`ProvidedMethods`] option.
+    ///
+    /// If the return type mentions `Self`, either bare or in `Option`,
+    /// `Result`, `Vec`, or tuples, the values of `Self` returned by a variant
+    /// are wrapped in the same variant.
+    ///
+    /// ```text
+    /// fn split(&self) -> Option<(Self, Self)> {
+    ///     match self {
+    ///         Enum::A(x) => x.split().map(|(a, b)| (Enum::A(a), Enum::A(b))),
+    ///         Enum::B(x) => x.split().map(|(a, b)| (Enum::B(a), Enum::B(b))),
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// If the method takes arguments of type `Self`, `&Self`, or `&mut Self`,
+    /// it delegates to the fields only if all of them are the same variant as
+    /// the receiver. Other combinations are handled according to
+    /// [`EnumImplOptions::mismatched_variants`].
+    ///
+    /// ```text
+    /// fn eq(&self, other: &Self) -> bool {
+    ///     match (self, other) {
+    ///         (Enum::A(x), Enum::A(y)) => x.eq(y),
+    ///         (Enum::B(x), Enum::B(y)) => x.eq(y),
+    ///         _ => false,
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// If the method is an `async fn`, the result of each variant is awaited.
+    /// If the method returns `impl Trait`, the results of the variants, which
+    /// have different types, are wrapped in an enum generated in the method
+    /// body. The enum implements `Iterator`, `DoubleEndedIterator`,
+    /// `ExactSizeIterator`, `FusedIterator`, and `Future` as needed.
+    ///
+    /// ```text
+    /// fn iter(&self) -> impl Iterator<Item = u8> {
+    ///     enum __Enum<__T0, __T1> { __V0(__T0), __V1(__T1) }
+    ///     impl<__T0, __T1> Iterator for __Enum<__T0, __T1> where /* ... */ { /* ... */ }
+    ///     match self {
+    ///         Enum::A(x) => __Enum::__V0(x.iter()),
+    ///         Enum::B(x) => __Enum::__V1(x.iter()),
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// Similarly, if the method returns `Self::X` and an enum is generated for
+    /// the associated type `X` (see [`EnumImplOptions::assoc_type_enum`]), the
+    /// results of the variants are wrapped in that enum.
+    ///
     /// # Panics
     ///
-    /// Panics if a trait method has a body, no receiver, or a receiver other
+    /// Panics if a required trait method has no receiver, or a receiver other
     /// than the following:
     ///
     /// - `&self`
     /// - `&mut self`
     /// - `self`
-    pub fn push_method(&mut self, item: TraitItemFn) {
-        assert!(item.default.is_none(), "method `{}` has a body", item.sig.ident);
+    /// - `self: Pin<&Self>`
+    /// - `self: Pin<&mut Self>`
+    /// - `self: Box<Self>`
+    ///
+    /// Methods with `self: Rc<Self>` or `self: Arc<Self>` receivers cannot be
+    /// delegated, so their bodies are `compile_error!` invocations.
+    ///
+    /// Also panics if the method returns `Self` and a variant to delegate to
+    /// has other fields than the field to delegate to, if the return type
+    /// mentions `Self` in other positions than those listed above (e.g.,
+    /// `Box<Self>` or `&Self`), or if the method returns `impl Trait` with a
+    /// trait other than those listed above and auto traits.
+    pub fn push_method(&mut self, mut item: TraitItemFn) {
+        if item.default.is_some() {
+            match self.options.provided_methods {
+                ProvidedMethods::Skip => return,
+                ProvidedMethods::Forward => {
+                    if matches!(ReceiverKind::try_new(&item.sig), Err(_) | Ok(ReceiverKind::Shared))
+                        || mentions_self(&item.sig)
+                    {
+                        return;
+                    }
+                    item.default = None;
+                }
+            }
+        }
+
+        if let Some(mut rename) = RenameShadowing::new(&item.sig.generics, &self.generics) {
+            rename.visit_signature_mut(&mut item.sig);
+        }
 
         let self_ty = ReceiverKind::new(&item.sig);
+        if let ReceiverKind::Shared = self_ty {
+            // The enum cannot be moved out of a shared pointer, so there is no
+            // way to create a shared pointer to the field.
+            let msg = format!(
+                "method `{}` cannot be delegated to the variants because its receiver is a shared pointer to `Self`; only `self: Box<Self>` is supported among smart pointers",
+                item.sig.ident
+            );
+            self.push_item(ImplItem::Fn(ImplItemFn {
+                attrs: item.attrs,
+                vis: Visibility::Inherited,
+                defaultness: None,
+                sig: item.sig,
+                block: parse_quote!({ ::core::compile_error!(#msg) }),
+            }));
+            return;
+        }
         let mut args = Vec::with_capacity(item.sig.inputs.len());
+        // Arguments of type `Self`, and the bindings of their fields.
+        let mut self_args = vec![];
+        let self_arg_count = item
+            .sig
+            .inputs
+            .iter()
+            .filter(|arg| matches!(arg, FnArg::Typed(arg) if is_self_arg(&arg.ty)))
+            .count();
         item.sig.inputs.iter().skip(1).for_each(|arg| match arg {
-            FnArg::Typed(arg) => args.push(&arg.pat),
+            FnArg::Typed(arg) if is_self_arg(&arg.ty) => {
+                let y = if self_arg_count == 1 {
+                    format_ident!("y")
+                } else {
+                    format_ident!("y{}", self_args.len())
+                };
+                args.push(y.to_token_stream());
+                self_args.push((&arg.pat, y));
+            }
+            FnArg::Typed(arg) => args.push(arg.pat.to_token_stream()),
             FnArg::Receiver(_) => panic!(
                 "method `{}` has a receiver in a position other than the first argument",
                 item.sig.ident
@@ -322,27 +894,185 @@ impl<'a> EnumImpl<'a> {
         });
 
         let method = &item.sig.ident;
-        let ident = &self.data.ident;
-        let method = match self_ty {
-            ReceiverKind::Normal => match &self.trait_ {
-                None => {
-                    let arms = self.data.variant_idents().map(|v| {
-                        quote! {
-                            #ident::#v(x) => x.#method(#(#args),*),
-                        }
+        let x = format_ident!("x");
+        let arms = self.data.arms();
+        // The enum that wraps the results of the variants if the method returns
+        // `impl Trait`, and its impls.
+        let opaque = format_ident!("__{}", self.data.ident());
+        let opaque_items =
+            opaque_enum(&item.sig, &opaque, arms.iter().filter(|arm| arm.field.is_some()).count());
+        // The enum that wraps the results of the variants, if any.
+        let wrapper = if opaque_items.is_some() {
+            Some(opaque.clone())
+        } else {
+            self.assoc_type_enum_of(&item.sig.output)
+        };
+        let convert = self.assoc_type_conversion(&item.sig.output);
+        // Converts the result of the method of the variant to the result of
+        // the method of the enum.
+        let rewrap = |arm: &Arm<'_>, mut expr: TokenStream| {
+            if item.sig.asyncness.is_some() {
+                expr = quote!(#expr.await);
+            }
+            if let Some(wrapper) = &wrapper {
+                let i = arms
+                    .iter()
+                    .filter(|arm| arm.field.is_some())
+                    .position(|a| ptr::eq(a, arm))
+                    .unwrap();
+                let variant = format_ident!("__V{}", i);
+                return quote!(#wrapper::#variant(#expr));
+            }
+            match &item.sig.output {
+                ReturnType::Type(_, ty) => {
+                    let wrap = |expr| {
+                        arm.wrap(&expr).unwrap_or_else(|| {
+                            panic!(
+                                "method `{}` returns `Self`, but a variant has fields other than the field to delegate to",
+                                item.sig.ident
+                            )
+                        })
+                    };
+                    let expr = rewrap(ty, expr, &wrap).unwrap_or_else(|| {
+                        panic!(
+                            "method `{}` returns `{}`, which cannot be converted from the results of the variants; `Self` is only supported bare and in `Option`, `Result`, `Vec`, and tuples",
+                            item.sig.ident,
+                            ty.to_token_stream()
+                        )
                     });
-                    parse_quote!(match self { #(#arms)* })
+                    match convert {
+                        Some(convert) => convert(expr),
+                        None => expr,
+                    }
                 }
-                Some(trait_) => {
-                    let arms =
-                        self.data.variant_idents().zip(self.data.field_types()).map(|(v, ty)| {
+                ReturnType::Default => expr,
+            }
+        };
+        // The receiver to match on, and the receiver to pass to the method of
+        // the field bound to `x`.
+        let (receiver, x_arg) = match &self_ty {
+            ReceiverKind::Normal => (quote!(self), quote!(x)),
+            ReceiverKind::Box(path) => (quote!(*self), quote!(#path::new(x))),
+            ReceiverKind::Shared => unreachable!(),
+            ReceiverKind::PinMut => {
+                self.pinned = true;
+                (
+                    quote!(unsafe { ::core::pin::Pin::get_unchecked_mut(self) }),
+                    quote!((unsafe { ::core::pin::Pin::new_unchecked(x) })),
+                )
+            }
+            ReceiverKind::PinRef => {
+                self.pinned = true;
+                (
+                    quote!(::core::pin::Pin::get_ref(self)),
+                    quote!((unsafe { ::core::pin::Pin::new_unchecked(x) })),
+                )
+            }
+        };
+        let scrutinee = match item.sig.receiver() {
+            // The match on an uninhabited enum needs to be on the value, not a reference.
+            Some(receiver) if arms.is_empty() && is_reference(receiver) => {
+                quote!(*self)
+            }
+            _ if arms.is_empty() && !matches!(self_ty, ReceiverKind::Normal) => quote!(*self),
+            _ if arms.is_empty() || self_args.is_empty() => receiver,
+            _ => {
+                let self_args = self_args.iter().map(|(pat, _)| pat);
+                quote!((#receiver, #(#self_args),*))
+            }
+        };
+        // Matches the variants of the receiver and the arguments of type `Self`
+        // at the same time, so only arms where all of them are the same variant
+        // delegate to the fields.
+        let pat = |arm: &Arm<'_>| {
+            let pat = arm.pat(&x);
+            if self_args.is_empty() {
+                pat
+            } else {
+                let self_args = self_args.iter().map(|(_, y)| arm.pat(y));
+                quote!((#pat, #(#self_args),*))
+            }
+        };
+        let mismatch = (arms.len() > 1 && !self_args.is_empty()).then(|| {
+            let expr =
+                match self.options.mismatched_variants.iter().find(|(name, _)| method == name) {
+                    Some((_, MismatchedVariants::Fallback(fallback))) => fallback.to_token_stream(),
+                    Some((_, MismatchedVariants::CompareIndices)) => {
+                        let index = |binding: &Ident| {
+                            let arms = arms.iter().enumerate().map(|(i, arm)| {
+                                let cfg_attrs = &arm.cfg_attrs;
+                                let pat = arm.pat_rest();
+                                quote!(#(#cfg_attrs)* #pat => #i,)
+                            });
+                            quote!(match #binding { #(#arms)* })
+                        };
+                        let (a, b) = (format_ident!("__a"), format_ident!("__b"));
+                        let (index_a, index_b) = (index(&a), index(&b));
+                        let mut expr = quote!(::core::cmp::Ord::cmp(&#index_a, &#index_b));
+                        if let ReturnType::Type(_, ty) = &item.sig.output {
+                            if ty::prelude_type_args(ty, "Option").is_some() {
+                                expr = quote!(::core::option::Option::Some(#expr));
+                            }
+                        }
+                        return quote! {
+                            #[allow(unreachable_patterns)]
+                            (#a, #b, ..) => #expr,
+                        };
+                    }
+                    None => {
+                        let msg = format!("`{method}` called with different variants");
+                        quote!(::core::panic!(#msg))
+                    }
+                };
+            quote! {
+                #[allow(unreachable_patterns)]
+                _ => #expr,
+            }
+        });
+        let method = match &self.trait_ {
+            None => {
+                let arms = arms.iter().map(|arm| {
+                    let pat = pat(arm);
+                    let cfg_attrs = &arm.cfg_attrs;
+                    match arm.field {
+                        Some(_) => {
+                            let expr = rewrap(arm, quote!(#x_arg.#method(#(#args),*)));
                             quote! {
-                                #ident::#v(x) => <#ty as #trait_>::#method(x #(,#args)*),
+                                #(#cfg_attrs)* #pat => #expr,
                             }
-                        });
-                    parse_quote!(match self { #(#arms)* })
-                }
-            },
+                        }
+                        None => {
+                            let fallback = arm.fallback;
+                            quote! {
+                                #(#cfg_attrs)* #pat => #fallback,
+                            }
+                        }
+                    }
+                });
+                parse_quote!(match #scrutinee { #(#arms)* #mismatch })
+            }
+            Some(trait_) => {
+                let arms = arms.iter().map(|arm| {
+                    let pat = pat(arm);
+                    let cfg_attrs = &arm.cfg_attrs;
+                    match arm.field {
+                        Some((_, ty)) => {
+                            let expr =
+                                rewrap(arm, quote!(<#ty as #trait_>::#method(#x_arg #(,#args)*)));
+                            quote! {
+                                #(#cfg_attrs)* #pat => #expr,
+                            }
+                        }
+                        None => {
+                            let fallback = arm.fallback;
+                            quote! {
+                                #(#cfg_attrs)* #pat => #fallback,
+                            }
+                        }
+                    }
+                });
+                parse_quote!(match #scrutinee { #(#arms)* #mismatch })
+            }
         };
 
         self.push_item(ImplItem::Fn(ImplItemFn {
@@ -350,9 +1080,20 @@ impl<'a> EnumImpl<'a> {
             vis: Visibility::Inherited,
             defaultness: None,
             sig: item.sig,
-            block: Block {
-                brace_token: token::Brace::default(),
-                stmts: vec![Stmt::Expr(method, None)],
+            block: match opaque_items {
+                Some(items) => {
+                    let result = format_ident!("__result");
+                    let unify = unify_gated_variants(&opaque, &arms, &result);
+                    if unify.is_empty() {
+                        parse_quote!({ #items #method })
+                    } else {
+                        parse_quote!({ #items let #result = #method; #(#unify)* #result })
+                    }
+                }
+                None => Block {
+                    brace_token: token::Brace::default(),
+                    stmts: vec![Stmt::Expr(method, None)],
+                },
             },
         }));
     }
@@ -361,31 +1102,429 @@ impl<'a> EnumImpl<'a> {
     ///
     /// # Panics
     ///
-    /// Panics if a trait method has a body, no receiver, or a receiver other
+    /// Panics if a required trait method has no receiver, or a receiver other
     /// than the following:
     ///
     /// - `&self`
     /// - `&mut self`
     /// - `self`
+    /// - `self: Pin<&Self>`
+    /// - `self: Pin<&mut Self>`
+    /// - `self: Box<Self>`
+    ///
+    /// Methods with `self: Rc<Self>` or `self: Arc<Self>` receivers cannot be
+    /// delegated, so their bodies are `compile_error!` invocations.
+    ///
+    /// Also panics if the trait has associated types or constants and the enum
+    /// has no variants to delegate to, if an enum is generated for a generic
+    /// associated type or for an associated type with a bound that the enum
+    /// cannot implement (see [`EnumImplOptions::assoc_type_enum`]), or if the
+    /// target type of a generic associated type is given (see
+    /// [`EnumImplOptions::assoc_type_target`]).
     pub fn append_items_from_trait(&mut self, trait_def: ItemTrait) {
-        let fst = self.data.field_types().next();
+        let fst = self.data.first_field_type();
         trait_def.items.into_iter().for_each(|item| match item {
-            // The TraitItemType::generics field (Generic associated types (GAT)) are not supported
-            TraitItem::Type(TraitItemType { ident, .. }) => {
+            TraitItem::Type(item)
+                if self.options.assoc_type_enums.iter().any(|name| item.ident == name) =>
+            {
+                self.push_assoc_type_enum(item);
+            }
+            TraitItem::Type(TraitItemType { ident, generics, .. })
+                if self.options.assoc_type_targets.iter().any(|(name, _)| ident == name) =>
+            {
+                assert!(
+                    generics.params.is_empty(),
+                    "target type cannot be set for generic associated type `{ident}`"
+                );
+                let (_, target) =
+                    self.options.assoc_type_targets.iter().find(|(name, _)| ident == name).unwrap();
+                let ty: ImplItemType = parse_quote!(type #ident = #target;);
+                self.push_item(ImplItem::Type(ty));
+            }
+            TraitItem::Type(TraitItemType { ident, mut generics, .. }) => {
+                assert!(
+                    fst.is_some(),
+                    "associated type `{ident}` cannot be delegated for enums without variants to delegate to"
+                );
+                if let Some(mut rename) = RenameShadowing::new(&generics, &self.generics) {
+                    rename.visit_generics_mut(&mut generics);
+                }
                 let trait_ = &self.trait_;
-                let ty = parse_quote!(type #ident = <#fst as #trait_>::#ident;);
+                let ty_generics = generics.split_for_impl().1;
+                let where_clause = &generics.where_clause;
+                let ty: ImplItemType = parse_quote! {
+                    type #ident #generics = <#fst as #trait_>::#ident #ty_generics #where_clause;
+                };
                 self.push_item(ImplItem::Type(ty));
             }
             TraitItem::Fn(method) => self.push_method(method),
+            TraitItem::Const(item) => self.push_const(item),
             _ => {}
         });
     }
 
-    pub fn build(self) -> TokenStream {
-        self.build_impl().to_token_stream()
+    /// Appends an associated type whose value is an enum of the associated
+    /// types of the variants, and generates the enum.
+    ///
+    /// See [`EnumImplOptions::assoc_type_enum`] for details.
+    fn push_assoc_type_enum(&mut self, item: TraitItemType) {
+        let TraitItemType { ident, generics, bounds, .. } = item;
+        assert!(
+            generics.params.is_empty(),
+            "enum cannot be generated for generic associated type `{ident}`"
+        );
+        let fields = self.data.fields();
+        assert!(
+            !fields.is_empty(),
+            "associated type `{ident}` cannot be delegated for enums without variants to delegate to"
+        );
+
+        let enum_ident = format_ident!("{}{}", self.data.ident(), ident);
+        let data = EnumData::anonymous_builder(enum_ident.clone(), fields.len())
+            .vis(self.data.vis().clone())
+            .build();
+        data.to_tokens(&mut self.extra_items);
+        self.extra_items.extend(enum_trait_impls(&data, &bounds, &|path| {
+            format!(
+                "associated type `{}` has bound `{}`, which cannot be implemented for the generated enum",
+                ident,
+                path.to_token_stream()
+            )
+        }));
+
+        let trait_ = &self.trait_;
+        let types: Vec<(Type, Vec<Attribute>)> = fields
+            .into_iter()
+            .map(|(ty, cfg_attrs)| {
+                (parse_quote!(<#ty as #trait_>::#ident), cfg_attrs.into_iter().cloned().collect())
+            })
+            .collect();
+        let args = types.iter().map(|(ty, _)| ty);
+        self.push_item(parse_quote!(type #ident = #enum_ident<#(#args),*>;));
+        if types.iter().any(|(_, cfg_attrs)| !cfg_attrs.is_empty()) {
+            self.gated_assoc_types.push((ident, enum_ident, types));
+        }
+    }
+
+    /// Returns the enum generated for the associated type if the method
+    /// returns `Self::X` and an enum is generated for `X`.
+    fn assoc_type_enum_of(&self, output: &ReturnType) -> Option<Ident> {
+        let ReturnType::Type(_, ty) = output else { return None };
+        let ident = self_assoc_type(ty)?;
+        self.options
+            .assoc_type_enums
+            .iter()
+            .any(|name| ident == name)
+            .then(|| format_ident!("{}{}", self.data.ident(), ident))
+    }
+
+    /// Returns a function that converts the result of the method of a variant
+    /// if the method returns `Self::X` or `Result<_, Self::X>` and the target
+    /// type of `X` is given by [`EnumImplOptions::assoc_type_target`].
+    fn assoc_type_conversion(&self, output: &ReturnType) -> Option<fn(TokenStream) -> TokenStream> {
+        let ReturnType::Type(_, ty) = output else { return None };
+        let is_target = |ty: &Type| {
+            self_assoc_type(ty).is_some_and(|ident| {
+                self.options.assoc_type_targets.iter().any(|(name, _)| ident == name)
+            })
+        };
+        if is_target(ty) {
+            Some(|expr| quote!(::core::convert::Into::into(#expr)))
+        } else if matches!(ty::prelude_type_args(ty, "Result").as_deref(), Some([_, e]) if is_target(e))
+        {
+            Some(|expr| quote!(::core::result::Result::map_err(#expr, ::core::convert::Into::into)))
+        } else {
+            None
+        }
+    }
+
+    /// Appends an associated constant to impl items.
+    ///
+    /// The constant is delegated to the first variant, unless its value is
+    /// given by [`EnumImplOptions::assoc_const_value`]. Constants that have a
+    /// default value or generics are skipped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the constant needs to be delegated and the enum has no
+    /// variants to delegate to.
+    pub fn push_const(&mut self, item: TraitItemConst) {
+        if item.default.is_some() || !item.generics.params.is_empty() {
+            return;
+        }
+        let TraitItemConst { attrs, ident, ty, .. } = item;
+
+        let value = self.options.assoc_const_values.iter().find(|(name, _)| ident == name);
+        let expr: Expr = if let Some((_, value)) = value {
+            value.clone()
+        } else {
+            let fields = self.data.fields();
+            let fst = self.data.first_field_type();
+            let Some(fst) = fst else {
+                panic!(
+                    "associated constant `{ident}` cannot be delegated for enums without variants to delegate to"
+                )
+            };
+            let trait_ = &self.trait_;
+            let value = quote!(<#fst as #trait_>::#ident);
+            match self.options.check_assoc_consts.then(|| const_eq(&ty)).flatten() {
+                Some(eq) => {
+                    let self_ty = &self.self_ty;
+                    let fst_key = TypeKey::new(fst, self_ty);
+                    let mut seen = BTreeSet::new();
+                    let asserts = fields.iter().filter_map(|(variant, cfg_attrs)| {
+                        let key = TypeKey::new(variant, self_ty);
+                        if key == fst_key || !seen.insert(key) {
+                            return None;
+                        }
+                        let cond = eq(quote!(<#variant as #trait_>::#ident), value.clone());
+                        let msg = format!(
+                            "associated constant `{}` of `{}` differs from that of `{}`",
+                            ident,
+                            variant.to_token_stream(),
+                            fst.to_token_stream(),
+                        );
+                        Some(quote! {
+                            #(#cfg_attrs)*
+                            ::core::assert!(#cond, #msg);
+                        })
+                    });
+                    parse_quote!({ #(#asserts)* #value })
+                }
+                None => parse_quote!(#value),
+            }
+        };
+        self.push_item(ImplItem::Const(ImplItemConst {
+            attrs,
+            vis: Visibility::Inherited,
+            defaultness: None,
+            const_token: <Token![const]>::default(),
+            ident,
+            generics: Generics::default(),
+            colon_token: <Token![:]>::default(),
+            ty,
+            eq_token: <Token![=]>::default(),
+            expr,
+            semi_token: <Token![;]>::default(),
+        }));
     }
 
-    pub fn build_impl(self) -> ItemImpl {
+    /// Builds the impl.
+    ///
+    /// If the bounds of some variants are gated by `#[cfg]`, one impl is
+    /// emitted per combination of their `#[cfg]` conditions, each with only
+    /// the bounds of the variants that are active under that combination.
+    /// Since the number of combinations grows exponentially, a compile error is
+    /// emitted instead if there are more than four distinct conditions.
+    ///
+    /// Note that the `#[cfg]` attributes in the input of derive macros have
+    /// already been evaluated, so this only matters for attribute macros and
+    /// other macros that see `#[cfg]` attributes before they are evaluated.
+    ///
+    /// If a method has a `Pin` receiver, the generated method projects it to
+    /// the field with an `unsafe` block. To make this sound, the following
+    /// items are also emitted:
+    ///
+    /// - An `Unpin` impl for the enum that requires the types of the fields to
+    ///   delegate to to be `Unpin`. Since the `Unpin` impls conflict, only one
+    ///   impl with such methods can emit it for each enum; see
+    ///   [`EnumImplOptions::unpin_impl`].
+    /// - A check that the enum does not implement `Drop`.
+    ///
+    /// The enums generated for associated types (see
+    /// [`EnumImplOptions::assoc_type_enum`]) are also emitted.
+    pub fn build(mut self) -> TokenStream {
+        let mut extra_items = if self.pinned { self.pin_guards() } else { TokenStream::new() };
+        self.pinned = false;
+        extra_items.extend(mem::take(&mut self.extra_items));
+        let cfg_predicates = mem::take(&mut self.cfg_predicates);
+        let gated_assoc_types = mem::take(&mut self.gated_assoc_types);
+        if cfg_predicates.is_empty() && gated_assoc_types.is_empty() {
+            let mut tokens = self.build_impl().to_token_stream();
+            tokens.extend(extra_items);
+            return tokens;
+        }
+
+        // Deduplicate conditions so that variants with the same `#[cfg]`
+        // attributes share a single condition.
+        let mut conditions: Vec<(String, TokenStream)> = vec![];
+        let mut index = |cfg_attrs: &[Attribute]| {
+            let condition = cfg_condition(cfg_attrs);
+            let key = condition.to_string();
+            match conditions.iter().position(|(k, _)| *k == key) {
+                Some(index) => index,
+                None => {
+                    conditions.push((key, condition));
+                    conditions.len() - 1
+                }
+            }
+        };
+        let cfg_predicates: Vec<_> = cfg_predicates
+            .into_iter()
+            .map(|(cfg_attrs, predicate)| (index(&cfg_attrs), predicate))
+            .collect();
+        // The arguments for disabled variants are replaced with those of an
+        // enabled variant, since their types may not implement the trait.
+        let gated_assoc_types: Vec<_> = gated_assoc_types
+            .into_iter()
+            .map(|(ident, enum_ident, types)| {
+                let types: Vec<_> = types
+                    .into_iter()
+                    .map(|(ty, cfg_attrs)| ((!cfg_attrs.is_empty()).then(|| index(&cfg_attrs)), ty))
+                    .collect();
+                (ident, enum_ident, types)
+            })
+            .collect();
+        if conditions.len() > MAX_CFG_CONDITIONS {
+            return format_err!(
+                self.data.ident(),
+                "too many distinct cfg conditions on variants (at most {} are supported)",
+                MAX_CFG_CONDITIONS
+            )
+            .to_compile_error();
+        }
+
+        let base = self.build_impl();
+        let mut tokens = TokenStream::new();
+        for set in 0..1_usize << conditions.len() {
+            let mut item = base.clone();
+            let condition = conditions.iter().enumerate().map(|(i, (_, condition))| {
+                if set & (1 << i) == 0 { quote!(not(#condition)) } else { condition.clone() }
+            });
+            item.attrs.push(parse_quote!(#[cfg(all(#(#condition),*))]));
+            let where_clause = &mut item.generics.make_where_clause().predicates;
+            where_clause.extend(
+                cfg_predicates
+                    .iter()
+                    .filter(|(i, _)| set & (1 << i) != 0)
+                    .map(|(_, predicate)| predicate.clone()),
+            );
+            let enabled = |index: &Option<usize>| index.map_or(true, |i| set & (1 << i) != 0);
+            for (ident, enum_ident, types) in &gated_assoc_types {
+                let Some((_, enabled_ty)) = types.iter().find(|(i, _)| enabled(i)) else {
+                    continue;
+                };
+                let args = types.iter().map(|(i, ty)| if enabled(i) { ty } else { enabled_ty });
+                for impl_item in &mut item.items {
+                    match impl_item {
+                        ImplItem::Type(ty) if ty.ident == *ident => {
+                            ty.ty = parse_quote!(#enum_ident<#(#args),*>);
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            item.to_tokens(&mut tokens);
+        }
+        tokens.extend(extra_items);
+        tokens
+    }
+
+    /// Generates the items that make the projections of `Pin` receivers
+    /// sound: an `Unpin` impl that requires the fields to delegate to to be
+    /// `Unpin`, and a check that the enum does not implement `Drop`.
+    fn pin_guards(&self) -> TokenStream {
+        let generics = self.data.generics();
+        let self_ty = &self.self_ty;
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let drop_guard = quote! {
+            trait __MustNotImplDrop {}
+            #[allow(clippy::drop_bounds, drop_bounds)]
+            impl<T: ::core::ops::Drop> __MustNotImplDrop for T {}
+            impl #impl_generics __MustNotImplDrop for #self_ty #where_clause {}
+        };
+        // A marker of the `Unpin` impl, which impls that do not emit it refer
+        // to so that they fail to compile without it. The name is shown in the
+        // error, so it explains what is missing.
+        let marker = format_ident!(
+            "__unpin_impl_false_requires_another_impl_to_emit_Unpin_for_{}",
+            self.data.ident()
+        );
+        if self.options.skip_unpin_impl {
+            return quote! {
+                const _: () = {
+                    #drop_guard
+                    #marker
+                };
+            };
+        }
+
+        // The lifetime prevents the bound from being a trivial bound that
+        // fails to compile if the fields are not `Unpin`.
+        let pin = Lifetime::new("'__pin", Span::call_site());
+        let params = &generics.params;
+        let phantom = params.iter().filter_map(|param| match param {
+            GenericParam::Lifetime(param) => {
+                let lifetime = &param.lifetime;
+                Some(quote!(&#lifetime ()))
+            }
+            GenericParam::Type(param) => {
+                let ident = &param.ident;
+                Some(quote!(*const #ident))
+            }
+            GenericParam::Const(_) => None,
+        });
+        let fields = self.data.fields().into_iter().enumerate().map(|(i, (ty, cfg_attrs))| {
+            let field = format_ident!("__field{}", i);
+            quote!(#(#cfg_attrs)* #field: #ty)
+        });
+        let mut pinned_generics = generics.clone();
+        pinned_generics.params.insert(0, parse_quote!(#pin));
+        let pinned = {
+            let ty_generics = pinned_generics.split_for_impl().1;
+            quote!(__Pinned #ty_generics)
+        };
+        pinned_generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote!(#pinned: ::core::marker::Unpin));
+        let (pinned_impl_generics, _, pinned_where_clause) = pinned_generics.split_for_impl();
+        quote! {
+            #[doc(hidden)]
+            #[allow(dead_code, non_upper_case_globals)]
+            const #marker: () = ();
+            const _: () = {
+                #[allow(dead_code)]
+                struct __Pinned<#pin, #params> #where_clause {
+                    __pin: ::core::marker::PhantomData<(&#pin (), #(#phantom),*)>,
+                    #(#fields,)*
+                }
+                impl #pinned_impl_generics ::core::marker::Unpin for #self_ty
+                    #pinned_where_clause
+                {
+                }
+                #drop_guard
+            };
+        }
+    }
+
+    /// Builds a single impl.
+    ///
+    /// Unlike [`EnumImpl::build`], the bounds of variants gated by `#[cfg]`
+    /// are included unconditionally.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a method has a `Pin` receiver or an enum is generated for an
+    /// associated type, since the items they need cannot be emitted. Use
+    /// [`EnumImpl::build`] instead.
+    pub fn build_impl(mut self) -> ItemImpl {
+        assert!(
+            !self.pinned,
+            "`EnumImpl::build_impl` cannot be used for methods with `Pin` receivers; use `EnumImpl::build` instead"
+        );
+        assert!(
+            self.extra_items.is_empty(),
+            "`EnumImpl::build_impl` cannot be used with enums generated for associated types; use `EnumImpl::build` instead"
+        );
+        if !self.cfg_predicates.is_empty() {
+            let cfg_predicates = mem::take(&mut self.cfg_predicates);
+            self.generics
+                .make_where_clause()
+                .predicates
+                .extend(cfg_predicates.into_iter().map(|(_, predicate)| predicate));
+        }
         ItemImpl {
             attrs: vec![parse_quote!(#[automatically_derived])],
             defaultness: if self.defaultness { Some(<Token![default]>::default()) } else { None },
@@ -403,41 +1542,531 @@ impl<'a> EnumImpl<'a> {
 enum ReceiverKind {
     /// `&(mut) self`, `(mut) self`, `(mut) self: &(mut) Self`, or `(mut) self: Self`
     Normal,
+    /// `self: Pin<&mut Self>`
+    PinMut,
+    /// `self: Pin<&Self>`
+    PinRef,
+    /// `self: Box<Self>`, with the path to `Box`
+    Box(Path),
+    /// `self: Rc<Self>` or `self: Arc<Self>`, which cannot be delegated
+    Shared,
 }
 
 impl ReceiverKind {
     fn new(sig: &Signature) -> Self {
+        match Self::try_new(sig) {
+            Ok(kind) => kind,
+            Err(msg) => panic!("{msg}"),
+        }
+    }
+
+    fn try_new(sig: &Signature) -> Result<Self, String> {
         fn get_ty_path(ty: &Type) -> Option<&Path> {
             if let Type::Path(TypePath { qself: None, path }) = ty { Some(path) } else { None }
         }
 
         match sig.receiver() {
-            None => panic!("method `{}` has no receiver", sig.ident),
+            None => Err(format!("method `{}` has no receiver", sig.ident)),
             Some(receiver) => {
                 if receiver.colon_token.is_none() {
-                    return ReceiverKind::Normal;
+                    return Ok(ReceiverKind::Normal);
                 }
                 match &*receiver.ty {
-                    Type::Path(TypePath { qself: None, path }) => {
+                    Type::Path(TypePath { qself: None, path }) if path.is_ident("Self") => {
                         // (mut) self: Self
-                        if path.is_ident("Self") {
-                            return ReceiverKind::Normal;
+                        return Ok(ReceiverKind::Normal);
+                    }
+                    Type::Path(TypePath { qself: None, path }) => {
+                        let last = path.segments.last().unwrap();
+                        let arg = match &last.arguments {
+                            PathArguments::AngleBracketed(args) if args.args.len() == 1 => {
+                                args.args.first()
+                            }
+                            _ => None,
+                        };
+                        match arg {
+                            // self: Pin<&(mut) Self>
+                            Some(GenericArgument::Type(Type::Reference(ty)))
+                                if last.ident == "Pin"
+                                    && get_ty_path(&ty.elem)
+                                        .is_some_and(|path| path.is_ident("Self")) =>
+                            {
+                                return Ok(if ty.mutability.is_some() {
+                                    ReceiverKind::PinMut
+                                } else {
+                                    ReceiverKind::PinRef
+                                });
+                            }
+                            Some(GenericArgument::Type(ty))
+                                if get_ty_path(ty).is_some_and(|path| path.is_ident("Self")) =>
+                            {
+                                // self: Box<Self>
+                                if last.ident == "Box" {
+                                    let mut path = path.clone();
+                                    path.segments.last_mut().unwrap().arguments =
+                                        PathArguments::None;
+                                    return Ok(ReceiverKind::Box(path));
+                                }
+                                // self: Rc<Self>, self: Arc<Self>
+                                if last.ident == "Rc" || last.ident == "Arc" {
+                                    return Ok(ReceiverKind::Shared);
+                                }
+                            }
+                            _ => {}
                         }
                     }
                     Type::Reference(ty) => {
                         // (mut) self: &(mut) Self
                         if get_ty_path(&ty.elem).is_some_and(|path| path.is_ident("Self")) {
-                            return ReceiverKind::Normal;
+                            return Ok(ReceiverKind::Normal);
                         }
                     }
                     _ => {}
                 }
-                panic!(
+                Err(format!(
                     "method `{}` has unsupported receiver type: {}",
                     sig.ident,
                     receiver.ty.to_token_stream()
-                );
+                ))
+            }
+        }
+    }
+}
+
+fn is_reference(receiver: &Receiver) -> bool {
+    receiver.reference.is_some() || matches!(&*receiver.ty, Type::Reference(_))
+}
+
+/// Returns `true` if the type of the argument is `Self`, `&Self`, or
+/// `&mut Self`.
+fn is_self_arg(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(ty) => is_self_arg(&ty.elem) && !matches!(&*ty.elem, Type::Reference(_)),
+        Type::Paren(ty) => is_self_arg(&ty.elem),
+        Type::Group(ty) => is_self_arg(&ty.elem),
+        Type::Path(TypePath { qself: None, path }) => path.is_ident("Self"),
+        _ => false,
+    }
+}
+
+/// Renames generic parameters of a method or a generic associated type that
+/// have the same name as generic parameters of the impl, which would
+/// otherwise be an error.
+struct RenameShadowing {
+    idents: Vec<(Ident, Ident)>,
+    lifetimes: Vec<(Ident, Ident)>,
+}
+
+impl RenameShadowing {
+    /// Returns `None` if no parameters in `generics` need to be renamed.
+    fn new(generics: &Generics, impl_generics: &Generics) -> Option<Self> {
+        let impl_params: Vec<_> = impl_generics
+            .params
+            .iter()
+            .map(|param| match param {
+                GenericParam::Type(param) => &param.ident,
+                GenericParam::Lifetime(param) => &param.lifetime.ident,
+                GenericParam::Const(param) => &param.ident,
+            })
+            .collect();
+        let fresh = |ident: &Ident| {
+            let mut new = format_ident!("__{}", ident);
+            while impl_params.contains(&&new) {
+                new = format_ident!("_{}", new);
+            }
+            new
+        };
+        let mut rename = Self { idents: vec![], lifetimes: vec![] };
+        for param in &generics.params {
+            match param {
+                GenericParam::Type(TypeParam { ident, .. })
+                | GenericParam::Const(ConstParam { ident, .. }) => {
+                    if impl_params.contains(&ident) {
+                        rename.idents.push((ident.clone(), fresh(ident)));
+                    }
+                }
+                GenericParam::Lifetime(param) => {
+                    let ident = &param.lifetime.ident;
+                    if impl_params.contains(&ident) {
+                        rename.lifetimes.push((ident.clone(), fresh(ident)));
+                    }
+                }
+            }
+        }
+        if rename.idents.is_empty() && rename.lifetimes.is_empty() { None } else { Some(rename) }
+    }
+
+    fn find(renames: &[(Ident, Ident)], ident: &Ident) -> Option<Ident> {
+        renames.iter().find(|(old, _)| old == ident).map(|(_, new)| new.clone())
+    }
+}
+
+impl VisitMut for RenameShadowing {
+    fn visit_path_mut(&mut self, path: &mut Path) {
+        if path.leading_colon.is_none() {
+            if let Some(first) = path.segments.first_mut() {
+                if let Some(new) = Self::find(&self.idents, &first.ident) {
+                    first.ident = new;
+                }
+            }
+        }
+        visit_mut::visit_path_mut(self, path);
+    }
+
+    fn visit_type_param_mut(&mut self, param: &mut TypeParam) {
+        if let Some(new) = Self::find(&self.idents, &param.ident) {
+            param.ident = new;
+        }
+        visit_mut::visit_type_param_mut(self, param);
+    }
+
+    fn visit_const_param_mut(&mut self, param: &mut ConstParam) {
+        if let Some(new) = Self::find(&self.idents, &param.ident) {
+            param.ident = new;
+        }
+        visit_mut::visit_const_param_mut(self, param);
+    }
+
+    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+        if let Some(new) = Self::find(&self.lifetimes, &lifetime.ident) {
+            lifetime.ident = new;
+        }
+    }
+}
+
+/// Returns a function that generates a const expression comparing two values
+/// of type `ty`, or `None` if such a comparison is not supported.
+fn const_eq(ty: &Type) -> Option<fn(TokenStream, TokenStream) -> TokenStream> {
+    const PRIMITIVES: &[&str] = &[
+        "bool", "char", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64",
+        "u128", "usize",
+    ];
+    match ty {
+        Type::Path(TypePath { qself: None, path })
+            if PRIMITIVES.iter().any(|name| path.is_ident(name)) =>
+        {
+            Some(|a, b| quote!(#a == #b))
+        }
+        Type::Reference(TypeReference { mutability: None, elem, .. }) if matches!(&**elem, Type::Path(TypePath { qself: None, path }) if path.is_ident("str")) =>
+        {
+            // `str` cannot be compared by `==` in const contexts.
+            Some(|a, b| {
+                quote! {{
+                    let (a, b) = (#a.as_bytes(), #b.as_bytes());
+                    let mut eq = a.len() == b.len();
+                    let mut i = 0;
+                    while eq && i < a.len() {
+                        eq = a[i] == b[i];
+                        i += 1;
+                    }
+                    eq
+                }}
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Returns `true` if the signature mentions `Self` other than in the receiver,
+/// arguments of type `Self`, a return type that [`rewrap`] supports, `Self::Assoc` paths, or `Self`
+/// bounds in the `where`-clause.
+///
+/// The results of such methods cannot be produced by delegating to a variant.
+fn mentions_self(sig: &Signature) -> bool {
+    fn is_self(ty: &Type) -> bool {
+        matches!(ty, Type::Path(ty) if ty.qself.is_none() && ty.path.is_ident("Self"))
+    }
+
+    let mut visitor = MentionsSelf(false);
+    sig.inputs.iter().skip(1).for_each(|arg| match arg {
+        FnArg::Typed(arg) if is_self_arg(&arg.ty) => {}
+        _ => visitor.visit_fn_arg(arg),
+    });
+    if let ReturnType::Type(_, ty) = &sig.output {
+        if rewrap(ty, TokenStream::new(), &|expr| expr).is_none() {
+            visitor.visit_type(ty);
+        }
+    }
+    sig.generics.params.iter().for_each(|param| visitor.visit_generic_param(param));
+    if let Some(where_clause) = &sig.generics.where_clause {
+        for predicate in &where_clause.predicates {
+            match predicate {
+                WherePredicate::Type(predicate) if is_self(&predicate.bounded_ty) => {
+                    predicate.bounds.iter().for_each(|bound| visitor.visit_type_param_bound(bound));
+                }
+                _ => visitor.visit_where_predicate(predicate),
             }
         }
     }
+    visitor.0
+}
+
+/// Returns a condition that holds if all of the `#[cfg]` attributes are
+/// active.
+fn cfg_condition<'a, I: IntoIterator<Item = &'a Attribute>>(cfg_attrs: I) -> TokenStream {
+    let metas = cfg_attrs.into_iter().map(|attr| match &attr.meta {
+        Meta::List(list) => list.tokens.clone(),
+        meta => meta.to_token_stream(),
+    });
+    quote!(all(#(#metas),*))
+}
+
+/// Returns `X` if the type is `Self::X`.
+fn self_assoc_type(ty: &Type) -> Option<&Ident> {
+    let Type::Path(TypePath { qself: None, path }) = ty else { return None };
+    if path.segments.len() != 2
+        || path.segments[0].ident != "Self"
+        || !path.segments.iter().all(|s| s.arguments.is_none())
+    {
+        return None;
+    }
+    Some(&path.segments[1].ident)
+}
+
+/// Returns `true` if the type mentions `Self` other than in `Self::Assoc` and
+/// `<Self as Trait>::Assoc` paths.
+fn type_mentions_self(ty: &Type) -> bool {
+    let mut visitor = MentionsSelf(false);
+    visitor.visit_type(ty);
+    visitor.0
+}
+
+struct MentionsSelf(bool);
+
+impl Visit<'_> for MentionsSelf {
+    fn visit_type_path(&mut self, ty: &TypePath) {
+        match &ty.qself {
+            // `<Self as Trait>::Assoc` is the same as `Self::Assoc`.
+            Some(qself)
+                if matches!(
+                    &*qself.ty,
+                    Type::Path(TypePath { qself: None, path }) if path.is_ident("Self")
+                ) =>
+            {
+                self.visit_path(&ty.path);
+            }
+            _ => visit::visit_type_path(self, ty),
+        }
+    }
+
+    fn visit_path(&mut self, path: &Path) {
+        if path.is_ident("Self") {
+            self.0 = true;
+        }
+        visit::visit_path(self, path);
+    }
+
+    fn visit_macro(&mut self, _: &Macro) {
+        // Conservatively assume that macros mention `Self`.
+        self.0 = true;
+    }
+}
+
+/// Returns an expression that converts `expr`, the result of the method of a
+/// variant, to `ty`, the return type of the method of the enum, by passing the
+/// values of `Self` in it to `wrap`.
+///
+/// `Self` is supported bare and in `Option`, `Result`, `Vec`, and tuples.
+/// Returns `None` if `ty` mentions `Self` in other positions.
+fn rewrap(
+    ty: &Type,
+    expr: TokenStream,
+    wrap: &dyn Fn(TokenStream) -> TokenStream,
+) -> Option<TokenStream> {
+    if !type_mentions_self(ty) {
+        return Some(expr);
+    }
+    let x = format_ident!("x");
+    match ty {
+        Type::Paren(ty) => rewrap(&ty.elem, expr, wrap),
+        Type::Group(ty) => rewrap(&ty.elem, expr, wrap),
+        Type::Path(TypePath { qself: None, path }) if path.is_ident("Self") => Some(wrap(expr)),
+        Type::Tuple(ty) => {
+            let vars: Vec<_> = (0..ty.elems.len()).map(|i| format_ident!("__{}", i)).collect();
+            let elems = ty
+                .elems
+                .iter()
+                .zip(&vars)
+                .map(|(ty, var)| rewrap(ty, var.to_token_stream(), wrap))
+                .collect::<Option<Vec<_>>>()?;
+            Some(quote!({
+                let (#(#vars,)*) = #expr;
+                (#(#elems,)*)
+            }))
+        }
+        _ => {
+            if let Some([t]) = ty::prelude_type_args(ty, "Option").as_deref() {
+                let t = rewrap(t, quote!(#x), wrap)?;
+                Some(quote!(::core::option::Option::map(#expr, |#x| #t)))
+            } else if let Some([t, e]) = ty::prelude_type_args(ty, "Result").as_deref() {
+                let mut expr = expr;
+                if type_mentions_self(t) {
+                    let t = rewrap(t, quote!(#x), wrap)?;
+                    expr = quote!(::core::result::Result::map(#expr, |#x| #t));
+                }
+                if type_mentions_self(e) {
+                    let e = rewrap(e, quote!(#x), wrap)?;
+                    expr = quote!(::core::result::Result::map_err(#expr, |#x| #e));
+                }
+                Some(expr)
+            } else if let Some([t]) = ty::prelude_type_args(ty, "Vec").as_deref() {
+                let t = rewrap(t, quote!(#x), wrap)?;
+                Some(quote! {
+                    ::core::iter::Iterator::collect::<#ty>(::core::iter::Iterator::map(
+                        ::core::iter::IntoIterator::into_iter(#expr),
+                        |#x| #t,
+                    ))
+                })
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// Generates an enum named `ident` with `variants` variants, and impls of the
+/// traits in the return type of the method for it, if the method returns
+/// `impl Trait`.
+///
+/// The results of the variants have different types, so the method wraps
+/// them in this enum instead.
+fn opaque_enum(sig: &Signature, ident: &Ident, variants: usize) -> Option<TokenStream> {
+    let ReturnType::Type(_, ty) = &sig.output else { return None };
+    let Type::ImplTrait(ty) = &**ty else { return None };
+    if variants == 0 {
+        return None;
+    }
+    let data = EnumData::anonymous(ident.clone(), variants);
+    let mut tokens = data.to_token_stream();
+    tokens.extend(enum_trait_impls(&data, &ty.bounds, &|path| {
+        format!(
+            "method `{}` returns `impl {}`, which cannot be delegated to the variants",
+            sig.ident,
+            path.to_token_stream()
+        )
+    }));
+    Some(tokens)
+}
+
+/// Returns statements that infer the type parameters of `ident`, the enum
+/// generated for a method that returns `impl Trait`, for variants whose arms
+/// are gated by `#[cfg]`, since they cannot be inferred if the arms are
+/// disabled.
+///
+/// The type parameter of a disabled arm is unified with that of the first arm
+/// that is not gated by `#[cfg]`, if any.
+fn unify_gated_variants(ident: &Ident, arms: &[Arm<'_>], result: &Ident) -> Vec<TokenStream> {
+    let arms: Vec<_> = arms.iter().filter(|arm| arm.field.is_some()).collect();
+    let Some(reference) = arms.iter().position(|arm| arm.cfg_attrs.is_empty()) else {
+        return vec![];
+    };
+    let params: Vec<_> = (0..arms.len()).map(|i| format_ident!("__T{}", i)).collect();
+    arms.iter()
+        .enumerate()
+        .filter(|(_, arm)| !arm.cfg_attrs.is_empty())
+        .map(|(i, arm)| {
+            let condition = cfg_condition(arm.cfg_attrs.iter().copied());
+            let unified = params.iter().enumerate().filter(|&(j, _)| j != i).map(|(_, p)| p);
+            let args =
+                params.iter().enumerate().map(|(j, p)| if j == i { &params[reference] } else { p });
+            let ty = quote!(#ident<#(#args),*>);
+            quote! {
+                #[cfg(not(#condition))]
+                let #result = {
+                    fn __unify<#(#unified),*>(x: #ty) -> #ty {
+                        x
+                    }
+                    __unify(#result)
+                };
+            }
+        })
+        .collect()
+}
+
+/// Generates impls of the traits in `bounds` for `data`, an enum whose
+/// variants each have one field of their own type parameter.
+///
+/// # Panics
+///
+/// Panics with the message returned by `unsupported` if `bounds` has a trait
+/// bound other than the following:
+///
+/// - `Iterator`, `DoubleEndedIterator`, `ExactSizeIterator`, `FusedIterator`
+/// - `Future`
+/// - `Send`, `Sync`, `Unpin`, and `Sized`, which the enum implements if all
+///   variants do
+fn enum_trait_impls(
+    data: &EnumData,
+    bounds: &Punctuated<TypeParamBound, Token![+]>,
+    unsupported: &dyn Fn(&Path) -> String,
+) -> TokenStream {
+    const TRAITS: &[&str] =
+        &["Iterator", "DoubleEndedIterator", "ExactSizeIterator", "FusedIterator", "Future"];
+
+    let mut traits = vec![];
+    for bound in bounds {
+        let TypeParamBound::Trait(bound) = bound else { continue };
+        let name = &bound.path.segments.last().unwrap().ident;
+        if name == "Send" || name == "Sync" || name == "Unpin" || name == "Sized" {
+            continue;
+        }
+        let Some(&name) = TRAITS.iter().find(|trait_| name == *trait_) else {
+            panic!("{}", unsupported(&bound.path))
+        };
+        // The iterator traits other than `Iterator` need an impl of `Iterator`.
+        if name != "Future" && !traits.contains(&"Iterator") {
+            traits.push("Iterator");
+        }
+        if !traits.contains(&name) {
+            traits.push(name);
+        }
+    }
+
+    let item = format_ident!("Item");
+    let mut tokens = TokenStream::new();
+    for name in traits {
+        let (path, supertraits_types, trait_def): (Path, Option<Ident>, ItemTrait) = match name {
+            "Iterator" => (parse_quote!(::core::iter::Iterator), None, parse_quote! {
+                trait Iterator {
+                    type Item;
+                    fn next(&mut self) -> ::core::option::Option<Self::Item>;
+                    fn size_hint(&self) -> (usize, ::core::option::Option<usize>);
+                }
+            }),
+            "DoubleEndedIterator" => (
+                parse_quote!(::core::iter::DoubleEndedIterator),
+                Some(item.clone()),
+                parse_quote! {
+                    trait DoubleEndedIterator: Iterator {
+                        fn next_back(&mut self) -> ::core::option::Option<Self::Item>;
+                    }
+                },
+            ),
+            "ExactSizeIterator" => {
+                (parse_quote!(::core::iter::ExactSizeIterator), Some(item.clone()), parse_quote! {
+                    trait ExactSizeIterator: Iterator {
+                        fn len(&self) -> usize;
+                    }
+                })
+            }
+            "FusedIterator" => (
+                parse_quote!(::core::iter::FusedIterator),
+                Some(item.clone()),
+                parse_quote!(
+                    trait FusedIterator: Iterator {}
+                ),
+            ),
+            _ => (parse_quote!(::core::future::Future), None, parse_quote! {
+                trait Future {
+                    type Output;
+                    fn poll(
+                        self: ::core::pin::Pin<&mut Self>,
+                        cx: &mut ::core::task::Context<'_>,
+                    ) -> ::core::task::Poll<Self::Output>;
+                }
+            }),
+        };
+        tokens.extend(EnumImpl::from_trait(data, &path, supertraits_types, trait_def).build());
+    }
+    tokens
 }
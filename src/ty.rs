@@ -0,0 +1,147 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use alloc::{
+    string::{String, ToString as _},
+    vec::Vec,
+};
+
+use quote::ToTokens as _;
+use syn::{
+    GenericArgument, Path, PathArguments, Type, TypePath,
+    visit::{self, Visit},
+    visit_mut::{self, VisitMut},
+};
+
+/// Modules of the standard library that export types in the prelude, and the
+/// names of those types.
+const PRELUDE_TYPES: &[(&str, &str)] = &[
+    ("option", "Option"),
+    ("result", "Result"),
+    ("vec", "Vec"),
+    ("string", "String"),
+    ("boxed", "Box"),
+];
+
+/// A normalized form of a type, used to compare types structurally.
+///
+/// Two types have the same key if they are equal after the following
+/// normalizations:
+///
+/// - Parentheses and invisible groups around types are removed.
+/// - Leading `::` of paths is removed.
+/// - Paths of prelude types in `std`, `core`, or `alloc` are replaced with
+///   their names, e.g., `::std::vec::Vec<T>` is the same as `Vec<T>`.
+/// - `Self` is replaced with the self type.
+///
+/// This does not resolve names, so a type that shadows a prelude type is
+/// considered the same as the prelude type.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct TypeKey(String);
+
+impl TypeKey {
+    pub(crate) fn new(ty: &Type, self_ty: &Type) -> Self {
+        let mut ty = ty.clone();
+        Normalize { self_ty }.visit_type_mut(&mut ty);
+        Self(ty.to_token_stream().to_string())
+    }
+}
+
+/// Returns `true` if `ty` is or contains the self type, e.g., `Box<Tree<T>>`
+/// in `enum Tree<T>`.
+pub(crate) fn contains_self_type(ty: &Type, self_ty: &Type) -> bool {
+    struct ContainsSelfType<'a> {
+        key: TypeKey,
+        self_ty: &'a Type,
+        found: bool,
+    }
+
+    impl<'ast> Visit<'ast> for ContainsSelfType<'_> {
+        fn visit_type(&mut self, ty: &'ast Type) {
+            if self.found || TypeKey::new(ty, self.self_ty) == self.key {
+                self.found = true;
+                return;
+            }
+            visit::visit_type(self, ty);
+        }
+    }
+
+    let mut visitor =
+        ContainsSelfType { key: TypeKey::new(self_ty, self_ty), self_ty, found: false };
+    visitor.visit_type(ty);
+    visitor.found
+}
+
+struct Normalize<'a> {
+    self_ty: &'a Type,
+}
+
+impl VisitMut for Normalize<'_> {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        loop {
+            match ty {
+                Type::Paren(inner) => *ty = (*inner.elem).clone(),
+                Type::Group(inner) => *ty = (*inner.elem).clone(),
+                Type::Path(TypePath { qself: None, path }) if path.is_ident("Self") => {
+                    *ty = self.self_ty.clone();
+                    // The self type is already normalized enough.
+                    return;
+                }
+                _ => break,
+            }
+        }
+        visit_mut::visit_type_mut(self, ty);
+    }
+
+    fn visit_path_mut(&mut self, path: &mut Path) {
+        path.leading_colon = None;
+        if is_prelude_type(path) {
+            let last = path.segments.pop().unwrap().into_value();
+            path.segments.clear();
+            path.segments.push(last);
+        }
+        visit_mut::visit_path_mut(self, path);
+    }
+}
+
+/// Returns `true` if the path is `{std,core,alloc}::<module>::<Type>` of a
+/// prelude type. Only the last segment may have generic arguments.
+fn is_prelude_type(path: &Path) -> bool {
+    if path.segments.len() != 3 {
+        return false;
+    }
+    let krate = &path.segments[0];
+    let module = &path.segments[1];
+    let name = &path.segments[2];
+    if !matches!(krate.arguments, PathArguments::None)
+        || !matches!(module.arguments, PathArguments::None)
+    {
+        return false;
+    }
+    (krate.ident == "std" || krate.ident == "core" || krate.ident == "alloc")
+        && PRELUDE_TYPES.iter().any(|(m, n)| module.ident == m && name.ident == n)
+}
+
+/// Returns the type arguments of `ty` if it is the prelude type `name`,
+/// written either as `name<..>` or as its full path.
+pub(crate) fn prelude_type_args<'a>(ty: &'a Type, name: &str) -> Option<Vec<&'a Type>> {
+    let Type::Path(TypePath { qself: None, path }) = ty else { return None };
+    let last = path.segments.last()?;
+    if last.ident != name
+        || path.segments.len() != 1 && !is_prelude_type(path)
+        || path.segments.len() == 1 && path.leading_colon.is_some()
+    {
+        return None;
+    }
+    match &last.arguments {
+        PathArguments::AngleBracketed(args) => Some(
+            args.args
+                .iter()
+                .filter_map(|arg| match arg {
+                    GenericArgument::Type(ty) => Some(ty),
+                    _ => None,
+                })
+                .collect(),
+        ),
+        _ => None,
+    }
+}
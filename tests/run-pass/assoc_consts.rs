@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use example_derive::Name;
+
+trait Name {
+    const NAME: &'static str;
+    const ID: u32;
+    const VERSION: u8;
+    fn name(&self) -> &'static str;
+}
+
+struct Json;
+struct JsonV2;
+
+impl Name for Json {
+    const NAME: &'static str = "json";
+    const ID: u32 = 1;
+    const VERSION: u8 = 0;
+    fn name(&self) -> &'static str {
+        "json v0"
+    }
+}
+
+impl Name for JsonV2 {
+    const NAME: &'static str = "json";
+    const ID: u32 = 1;
+    const VERSION: u8 = 2;
+    fn name(&self) -> &'static str {
+        "json v2"
+    }
+}
+
+#[derive(Name)]
+enum Codec<A, B> {
+    A(A),
+    B(B),
+}
+
+const NAME: &str = <Codec<Json, JsonV2> as Name>::NAME;
+const ID: u32 = <Codec<Json, JsonV2> as Name>::ID;
+
+fn main() {
+    assert_eq!(NAME, "json");
+    assert_eq!(ID, 1);
+    assert_eq!(<Codec<Json, JsonV2> as Name>::VERSION, 1);
+    assert_eq!(Codec::<Json, JsonV2>::B(JsonV2).name(), "json v2");
+}
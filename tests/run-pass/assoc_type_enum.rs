@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+mod values {
+    use example_derive::IntoIterator;
+
+    #[derive(IntoIterator)]
+    pub enum Values<A, B, C> {
+        Vec(A),
+        Array(B),
+        Option(C),
+    }
+}
+
+use example_derive::enum_impl;
+use values::{Values, ValuesIntoIter};
+
+// The arguments of the generated enum for disabled variants are replaced.
+#[enum_impl(IntoIterator)]
+enum Gated<A, B> {
+    A(A),
+    #[cfg(any())]
+    B(B),
+    #[cfg(all())]
+    C(Vec<B>),
+    #[cfg(not(all()))]
+    D(u8),
+}
+
+fn main() {
+    type V = Values<Vec<u8>, [u8; 2], Option<u8>>;
+
+    let iter: ValuesIntoIter<_, _, _> = V::Vec(vec![1, 2, 3]).into_iter();
+    assert_eq!(iter.collect::<Vec<_>>(), [1, 2, 3]);
+    assert_eq!(V::Array([4, 5]).into_iter().collect::<Vec<_>>(), [4, 5]);
+    assert_eq!(V::Option(Some(6)).into_iter().collect::<Vec<_>>(), [6]);
+    assert_eq!(V::Option(None).into_iter().count(), 0);
+
+    let mut sum = 0;
+    for x in V::Array([1, 2]) {
+        sum += x;
+    }
+    assert_eq!(sum, 3);
+
+    let a = Gated::<Vec<u8>, u8>::A(vec![1, 2]);
+    assert_eq!(a.into_iter().collect::<Vec<_>>(), [1, 2]);
+    let c = Gated::<Vec<u8>, u8>::C(vec![3]);
+    assert_eq!(c.into_iter().collect::<Vec<_>>(), [3]);
+}
@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use example_derive::Decode;
+
+trait Decode: Sized {
+    type Error;
+    fn decode(&self, input: &[u8]) -> Result<u8, Self::Error>;
+    fn limit(self, max: u8) -> Result<Self, Self::Error>;
+    fn error(&self) -> Self::Error;
+}
+
+#[derive(Debug, PartialEq)]
+enum DecodeError {
+    Empty,
+    TooLarge(u8),
+}
+
+struct Empty;
+
+impl From<Empty> for DecodeError {
+    fn from(_: Empty) -> Self {
+        Self::Empty
+    }
+}
+
+struct TooLarge(u8);
+
+impl From<TooLarge> for DecodeError {
+    fn from(e: TooLarge) -> Self {
+        Self::TooLarge(e.0)
+    }
+}
+
+struct First;
+
+impl Decode for First {
+    type Error = Empty;
+    fn decode(&self, input: &[u8]) -> Result<u8, Empty> {
+        input.first().copied().ok_or(Empty)
+    }
+    fn limit(self, _: u8) -> Result<Self, Empty> {
+        Ok(self)
+    }
+    fn error(&self) -> Empty {
+        Empty
+    }
+}
+
+struct Max(u8);
+
+impl Decode for Max {
+    type Error = TooLarge;
+    fn decode(&self, input: &[u8]) -> Result<u8, TooLarge> {
+        let max = input.iter().copied().max().unwrap_or(0);
+        if max > self.0 { Err(TooLarge(max)) } else { Ok(max) }
+    }
+    fn limit(self, max: u8) -> Result<Self, TooLarge> {
+        if self.0 > max { Err(TooLarge(self.0)) } else { Ok(self) }
+    }
+    fn error(&self) -> TooLarge {
+        TooLarge(self.0)
+    }
+}
+
+#[derive(Decode)]
+enum Either<A, B> {
+    A(A),
+    B(B),
+}
+
+fn main() {
+    let a = Either::<First, Max>::A(First);
+    let b = Either::<First, Max>::B(Max(5));
+
+    assert_eq!(a.decode(&[3, 9]), Ok(3));
+    assert_eq!(a.decode(&[]), Err(DecodeError::Empty));
+    assert_eq!(b.decode(&[3, 4]), Ok(4));
+    assert_eq!(b.decode(&[3, 9]), Err(DecodeError::TooLarge(9)));
+
+    assert_eq!(a.error(), DecodeError::Empty);
+    assert_eq!(b.error(), DecodeError::TooLarge(5));
+
+    assert!(matches!(a.limit(1), Ok(Either::A(First))));
+    assert!(matches!(b.limit(1), Err(DecodeError::TooLarge(5))));
+    let b = Either::<First, Max>::B(Max(5));
+    assert!(matches!(b.limit(8), Ok(Either::B(Max(5)))));
+}
@@ -0,0 +1,69 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::cmp::Ordering;
+
+use example_derive::{Merge, PartialEq, PartialOrd};
+
+trait Merge {
+    fn merge(&mut self, other: Self);
+    fn merged(self, other: Self) -> Self;
+}
+
+impl Merge for Vec<u8> {
+    fn merge(&mut self, other: Self) {
+        self.extend(other);
+    }
+    fn merged(mut self, other: Self) -> Self {
+        self.merge(other);
+        self
+    }
+}
+
+impl Merge for String {
+    fn merge(&mut self, other: Self) {
+        self.push_str(&other);
+    }
+    fn merged(mut self, other: Self) -> Self {
+        self.merge(other);
+        self
+    }
+}
+
+#[derive(Debug, PartialEq, PartialOrd, Merge)]
+enum Value<A, B> {
+    A(A),
+    B { inner: B },
+}
+
+#[derive(PartialEq)]
+enum Single<A> {
+    A(A),
+}
+
+fn main() {
+    type V = Value<Vec<u8>, String>;
+
+    assert!(V::A(vec![1]) == V::A(vec![1]));
+    assert!(V::A(vec![1]) != V::A(vec![2]));
+    assert!(V::A(vec![]) != V::B { inner: String::new() });
+    assert!(Single::A(1) == Single::A(1));
+
+    assert_eq!(V::A(vec![1]).partial_cmp(&V::A(vec![2])), Some(Ordering::Less));
+    assert_eq!(
+        V::B { inner: "b".into() }.partial_cmp(&V::B { inner: "a".into() }),
+        Some(Ordering::Greater)
+    );
+    // Different variants are compared by their indices.
+    assert!(V::A(vec![2]) < V::B { inner: "a".into() });
+    assert!(V::B { inner: "a".into() } > V::A(vec![2]));
+
+    let mut v = V::A(vec![1]);
+    v.merge(V::A(vec![2]));
+    assert_eq!(v, V::A(vec![1, 2]));
+    assert_eq!(
+        V::B { inner: "a".into() }.merged(V::B { inner: "b".into() }),
+        V::B { inner: "ab".into() }
+    );
+    let result = std::panic::catch_unwind(|| V::A(vec![]).merged(V::B { inner: String::new() }));
+    assert!(result.is_err());
+}
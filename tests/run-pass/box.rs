@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use example_derive::Task;
+
+trait Task {
+    fn run(self: Box<Self>, input: u8) -> u8;
+}
+
+struct Add(u8);
+
+impl Task for Add {
+    fn run(self: Box<Self>, input: u8) -> u8 {
+        input + self.0
+    }
+}
+
+struct Double;
+
+impl Task for Double {
+    fn run(self: Box<Self>, input: u8) -> u8 {
+        input * 2
+    }
+}
+
+#[derive(Task)]
+enum Either<A, B> {
+    A(A),
+    B { inner: B },
+}
+
+fn main() {
+    let tasks: Vec<Box<dyn Task>> = vec![
+        Box::new(Either::<Add, Double>::A(Add(1))),
+        Box::new(Either::<Add, Double>::B { inner: Double }),
+    ];
+    assert_eq!(tasks.into_iter().fold(2, |acc, task| task.run(acc)), 6);
+}
@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use example_derive::{DefaultIterator, Iterator, enum_impl};
+
+struct NotIterator;
+
+#[derive(Iterator)]
+enum Either<A, B, C> {
+    A(A),
+    #[cfg(any())]
+    B(B),
+    #[cfg(all())]
+    C(C),
+    #[cfg(any())]
+    D(NotIterator),
+    #[allow(dead_code)]
+    E(std::marker::PhantomData<B>, #[delegate] A),
+}
+
+#[derive(DefaultIterator)]
+enum Source<A> {
+    #[cfg(any())]
+    Closed,
+    Empty,
+    #[cfg(all())]
+    File(A),
+}
+
+// The `#[cfg]` attributes in the input of derive macros have already been
+// evaluated, but attribute macros see them as written.
+#[enum_impl(Iterator)]
+enum Gated<A, B> {
+    A(A),
+    #[cfg(any())]
+    B(B),
+    #[cfg(all())]
+    C(std::iter::Map<std::vec::IntoIter<B>, fn(B) -> u8>),
+}
+
+// `#[cfg]` in `#[cfg_attr]` only removes the variant if the predicate holds.
+#[enum_impl(Iterator)]
+enum Attr<A, C> {
+    A(A),
+    #[cfg_attr(all(), cfg(any()))]
+    B(NotIterator),
+    #[cfg_attr(any(), cfg(any()))]
+    C(C),
+}
+
+fn main() {
+    let a = Either::<_, NotIterator, std::ops::Range<u8>>::A(0..3_u8);
+    assert_eq!(a.collect::<Vec<_>>(), [0, 1, 2]);
+    let c = Either::<std::vec::IntoIter<u8>, NotIterator, _>::C(vec![4, 5].into_iter());
+    assert_eq!(c.sum::<u8>(), 9);
+    let empty = Source::<std::ops::Range<u8>>::Empty;
+    assert_eq!(empty.count(), 0);
+    let file = Source::File(0..2_u8);
+    assert_eq!(file.count(), 2);
+
+    let a = Gated::<_, NotIterator>::A(0..3_u8);
+    assert_eq!(a.count(), 3);
+    let c = Gated::<std::ops::Range<u8>, NotIterator>::C(
+        vec![NotIterator, NotIterator].into_iter().map((|_| 1) as fn(NotIterator) -> u8),
+    );
+    assert_eq!(c.sum::<u8>(), 2);
+
+    let a = Attr::<_, std::ops::Range<u8>>::A(0..3_u8);
+    assert_eq!(a.count(), 3);
+    let c = Attr::<std::ops::Range<u8>, _>::C(0..4_u8);
+    assert_eq!(c.count(), 4);
+}
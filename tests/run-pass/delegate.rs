@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use example_derive::{ExactSizeIterator, Iterator};
+
+#[derive(Iterator, ExactSizeIterator)]
+enum Enum<A, B> {
+    A(#[delegate] A, &'static str),
+    B(u8, u16, #[delegate] B),
+    C(B),
+}
+
+fn main() {
+    let a = Enum::<_, std::ops::Range<usize>>::A(0..3, "a");
+    assert_eq!(a.len(), 3);
+    assert_eq!(a.sum::<usize>(), 3);
+    let b = Enum::<std::ops::Range<usize>, _>::B(1, 2, vec![4, 5].into_iter());
+    assert_eq!(b.len(), 2);
+    assert_eq!(b.sum::<usize>(), 9);
+    let c = Enum::<std::ops::Range<usize>, _>::C(2..3);
+    assert_eq!(c.collect::<Vec<_>>(), [2]);
+}
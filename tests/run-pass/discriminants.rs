@@ -0,0 +1,20 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use example_derive::DefaultIterator;
+
+#[derive(DefaultIterator)]
+#[repr(u8)]
+enum Enum<A, B> {
+    A(A) = 1,
+    B(B) = 4,
+    C = 8,
+}
+
+fn main() {
+    let a = Enum::<_, std::vec::IntoIter<u8>>::A(0..3);
+    assert_eq!(a.sum::<u8>(), 3);
+    let b = Enum::<std::ops::Range<u8>, _>::B(vec![4, 5].into_iter());
+    assert_eq!(b.sum::<u8>(), 9);
+    let c = Enum::<std::ops::Range<u8>, std::vec::IntoIter<u8>>::C;
+    assert_eq!(c.count(), 0);
+}
@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use example_derive::{LendingIterator, Parse};
+
+trait LendingIterator {
+    type Item<'a>
+    where
+        Self: 'a;
+    fn next(&mut self) -> Option<Self::Item<'_>>;
+}
+
+struct WindowsMut(Vec<u8>, usize);
+
+impl LendingIterator for WindowsMut {
+    type Item<'a> = &'a mut [u8];
+    fn next(&mut self) -> Option<Self::Item<'_>> {
+        let window = self.0.get_mut(self.1..self.1 + 2)?;
+        self.1 += 1;
+        Some(window)
+    }
+}
+
+struct Once(Vec<u8>, bool);
+
+impl LendingIterator for Once {
+    type Item<'a> = &'a mut [u8];
+    fn next(&mut self) -> Option<Self::Item<'_>> {
+        if self.1 {
+            return None;
+        }
+        self.1 = true;
+        Some(&mut self.0)
+    }
+}
+
+#[derive(LendingIterator)]
+enum Lending {
+    A(WindowsMut),
+    B(Once),
+}
+
+struct WindowsMutRef<'s>(&'s mut [u8], usize);
+
+impl LendingIterator for WindowsMutRef<'_> {
+    type Item<'a>
+        = &'a mut [u8]
+    where
+        Self: 'a;
+    fn next(&mut self) -> Option<Self::Item<'_>> {
+        let window = self.0.get_mut(self.1..self.1 + 2)?;
+        self.1 += 1;
+        Some(window)
+    }
+}
+
+struct OnceRef<'s>(&'s mut [u8], bool);
+
+impl LendingIterator for OnceRef<'_> {
+    type Item<'a>
+        = &'a mut [u8]
+    where
+        Self: 'a;
+    fn next(&mut self) -> Option<Self::Item<'_>> {
+        if self.1 {
+            return None;
+        }
+        self.1 = true;
+        Some(self.0)
+    }
+}
+
+// The variants borrow data, so they are not required to be `'static`.
+#[derive(LendingIterator)]
+enum LendingRef<'s> {
+    A(WindowsMutRef<'s>),
+    B(OnceRef<'s>),
+}
+
+// The variants are generic, so their `Item`s are required to be the same.
+#[derive(LendingIterator)]
+enum Either<A, B> {
+    A(A),
+    B(B),
+}
+
+trait Parse {
+    type Output<'a>;
+    fn parse<'a>(&self, input: &'a str) -> Self::Output<'a>;
+}
+
+struct First;
+
+impl Parse for First {
+    type Output<'a> = Option<&'a str>;
+    fn parse<'a>(&self, input: &'a str) -> Self::Output<'a> {
+        input.split(',').next()
+    }
+}
+
+struct Prefix<'s>(&'s str);
+
+impl Parse for Prefix<'_> {
+    type Output<'a> = Option<&'a str>;
+    fn parse<'a>(&self, input: &'a str) -> Self::Output<'a> {
+        input.strip_prefix(self.0)
+    }
+}
+
+// The lifetime parameter has the same name as the parameter of the GAT.
+#[derive(Parse)]
+enum Parser<'a> {
+    A(First),
+    B(Prefix<'a>),
+}
+
+fn main() {
+    let mut iter = Lending::A(WindowsMut(vec![1, 2, 3], 0));
+    while let Some(window) = iter.next() {
+        window[1] += window[0];
+    }
+    let Lending::A(WindowsMut(buf, _)) = iter else { unreachable!() };
+    assert_eq!(buf, [1, 3, 6]);
+    let mut iter = Lending::B(Once(vec![1], false));
+    assert_eq!(iter.next().unwrap(), [1]);
+    assert!(iter.next().is_none());
+
+    let mut buf = vec![1, 2, 3];
+    let mut iter = LendingRef::A(WindowsMutRef(&mut buf, 0));
+    while let Some(window) = iter.next() {
+        window[1] += window[0];
+    }
+    drop(iter);
+    assert_eq!(buf, [1, 3, 6]);
+    let mut iter = LendingRef::B(OnceRef(&mut buf, false));
+    assert_eq!(iter.next().unwrap(), [1, 3, 6]);
+    assert!(iter.next().is_none());
+
+    assert_eq!(Parser::A(First).parse("a,b"), Some("a"));
+    assert_eq!(Parser::B(Prefix("a,")).parse("a,b"), Some("b"));
+
+    let mut iter = Either::<WindowsMut, Once>::A(WindowsMut(vec![1, 2, 3], 0));
+    while let Some(window) = iter.next() {
+        window[0] *= 2;
+    }
+    let Either::A(WindowsMut(buf, _)) = iter else { unreachable!() };
+    assert_eq!(buf, [2, 4, 3]);
+    let mut iter = Either::<WindowsMut, Once>::B(Once(vec![4, 5], false));
+    assert_eq!(iter.next().unwrap(), [4, 5]);
+    assert!(iter.next().is_none());
+}
@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use example_derive::Iterator;
+
+struct Countdown<const N: usize>(u8);
+
+// Implemented only for a specific value of the const parameter.
+impl Iterator for Countdown<3> {
+    type Item = u8;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0 = self.0.checked_sub(1)?;
+        Some(self.0)
+    }
+}
+
+#[derive(Iterator)]
+enum Const<const N: usize> {
+    A(Countdown<N>),
+    B(std::array::IntoIter<u8, N>),
+}
+
+#[derive(Iterator)]
+enum Lifetime<'a> {
+    A(std::slice::Iter<'a, u8>),
+    B(std::iter::Rev<std::slice::Iter<'a, u8>>),
+}
+
+// https://github.com/taiki-e/derive_utils/issues/47
+#[derive(Iterator)]
+enum Tree<'a> {
+    Leaf(std::slice::Iter<'a, u8>),
+    Node(Box<Tree<'a>>),
+}
+
+#[derive(Iterator)]
+enum Nested<T> {
+    Node(Box<Self>),
+    Leaf(T),
+}
+
+fn main() {
+    let a = Const::<3>::A(Countdown(3));
+    assert_eq!(a.collect::<Vec<_>>(), [2, 1, 0]);
+    let b = Const::<3>::B([4, 5, 6].into_iter());
+    assert_eq!(b.sum::<u8>(), 15);
+
+    let v = [1, 2, 3];
+    let a = Lifetime::A(v.iter());
+    assert_eq!(a.copied().collect::<Vec<_>>(), [1, 2, 3]);
+    let b = Lifetime::B(v.iter().rev());
+    assert_eq!(b.copied().collect::<Vec<_>>(), [3, 2, 1]);
+
+    let tree = Tree::Node(Box::new(Tree::Node(Box::new(Tree::Leaf(v.iter())))));
+    assert_eq!(tree.copied().collect::<Vec<_>>(), [1, 2, 3]);
+    let nested = Nested::Node(Box::new(Nested::Leaf(0..3_u8)));
+    assert_eq!(nested.sum::<u8>(), 3);
+}
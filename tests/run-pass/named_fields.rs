@@ -0,0 +1,23 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use example_derive::{ExactSizeIterator, Iterator};
+
+#[derive(Iterator, ExactSizeIterator)]
+enum Enum<A, B> {
+    A {
+        #[delegate]
+        inner: A,
+        meta: &'static str,
+    },
+    B {
+        inner: B,
+    },
+}
+
+fn main() {
+    let a = Enum::<_, std::vec::IntoIter<u8>>::A { inner: 0..3, meta: "a" };
+    assert_eq!(a.len(), 3);
+    assert_eq!(a.sum::<u8>(), 3);
+    let b = Enum::<std::ops::Range<u8>, _>::B { inner: vec![4, 5].into_iter() };
+    assert_eq!(b.sum::<u8>(), 9);
+}
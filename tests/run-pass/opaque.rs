@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::{
+    future::{self, Future},
+    marker::PhantomData,
+    pin::pin,
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+};
+
+use example_derive::{Source, enum_impl};
+
+trait Source {
+    async fn fetch(&self, offset: u8) -> u8;
+    fn values(&self) -> impl DoubleEndedIterator<Item = u8> + ExactSizeIterator + '_;
+    fn ready(&self) -> impl Future<Output = u8> + Send;
+}
+
+struct Bytes(Vec<u8>);
+
+impl Source for Bytes {
+    async fn fetch(&self, offset: u8) -> u8 {
+        self.0[0] + offset
+    }
+    fn values(&self) -> impl DoubleEndedIterator<Item = u8> + ExactSizeIterator + '_ {
+        self.0.iter().copied()
+    }
+    fn ready(&self) -> impl Future<Output = u8> + Send {
+        future::ready(self.0.len() as u8)
+    }
+}
+
+struct Range(u8);
+
+impl Source for Range {
+    async fn fetch(&self, offset: u8) -> u8 {
+        future::ready(self.0).await + offset
+    }
+    fn values(&self) -> impl DoubleEndedIterator<Item = u8> + ExactSizeIterator + '_ {
+        0..self.0
+    }
+    fn ready(&self) -> impl Future<Output = u8> + Send {
+        let n = self.0;
+        async move { n }
+    }
+}
+
+#[derive(Source)]
+enum Either<A, B> {
+    A(A),
+    B(B),
+}
+
+trait Values {
+    fn values(&self) -> impl Iterator<Item = u8> + '_;
+}
+
+impl Values for Vec<u8> {
+    fn values(&self) -> impl Iterator<Item = u8> + '_ {
+        self.iter().copied()
+    }
+}
+
+impl Values for u8 {
+    fn values(&self) -> impl Iterator<Item = u8> + '_ {
+        0..*self
+    }
+}
+
+impl<T> Values for PhantomData<T> {
+    fn values(&self) -> impl Iterator<Item = u8> + '_ {
+        std::iter::empty()
+    }
+}
+
+// The variants of the generated enum for disabled arms are never constructed.
+#[enum_impl(Values)]
+enum Gated<A, B> {
+    A(A),
+    #[cfg(any())]
+    B(B),
+    #[cfg(all())]
+    C(u8),
+    D(PhantomData<B>),
+}
+
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+fn block_on<F: Future>(fut: F) -> F::Output {
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut cx = Context::from_waker(&waker);
+    let mut fut = pin!(fut);
+    loop {
+        if let Poll::Ready(output) = fut.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+fn assert_send<T: Send>(_: &T) {}
+
+fn main() {
+    let a = Either::<Bytes, Range>::A(Bytes(vec![1, 2, 3]));
+    let b = Either::<Bytes, Range>::B(Range(2));
+
+    assert_eq!(block_on(a.fetch(1)), 2);
+    assert_eq!(block_on(b.fetch(1)), 3);
+
+    assert_eq!(a.values().rev().collect::<Vec<_>>(), [3, 2, 1]);
+    assert_eq!(b.values().len(), 2);
+
+    let fut = b.ready();
+    assert_send(&fut);
+    assert_eq!(block_on(fut), 2);
+    assert_eq!(block_on(a.ready()), 3);
+
+    let a = Gated::<Vec<u8>, u8>::A(vec![1, 2]);
+    assert_eq!(a.values().collect::<Vec<_>>(), [1, 2]);
+    let c = Gated::<Vec<u8>, u8>::C(3);
+    assert_eq!(c.values().collect::<Vec<_>>(), [0, 1, 2]);
+    let d = Gated::<Vec<u8>, u8>::D(PhantomData);
+    assert_eq!(d.values().count(), 0);
+}
@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use example_derive::Len;
+
+trait Len {
+    fn len(&self) -> usize;
+}
+
+impl<T> Len for Vec<T> {
+    fn len(&self) -> usize {
+        self.len()
+    }
+}
+
+#[derive(Len)]
+enum Never {}
+
+#[derive(Len)]
+enum Units {
+    A,
+    B(),
+}
+
+#[derive(Len)]
+enum Enum<A> {
+    A(#[inner] A, &'static str),
+    B { inner: Vec<u8> },
+    C,
+}
+
+fn main() {
+    let _: Option<Never> = None;
+    assert_eq!(Units::A.len(), 0);
+    assert_eq!(Units::B().len(), 0);
+    assert_eq!(Enum::A(vec![1, 2, 3], "a").len(), 3);
+    assert_eq!(Enum::<Vec<u8>>::B { inner: vec![1] }.len(), 1);
+    assert_eq!(Enum::<Vec<u8>>::C.len(), 0);
+}
@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::{
+    future::{self, Future as _},
+    marker::PhantomPinned,
+    pin::{Pin, pin},
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+};
+
+use example_derive::{Future, PinnedLen, PinnedName};
+
+trait PinnedLen {
+    fn len(self: Pin<&Self>) -> usize;
+}
+
+struct Pinned(usize, PhantomPinned);
+
+impl PinnedLen for Pinned {
+    fn len(self: Pin<&Self>) -> usize {
+        self.0
+    }
+}
+
+impl PinnedLen for String {
+    fn len(self: Pin<&Self>) -> usize {
+        self.get_ref().len()
+    }
+}
+
+#[derive(Future)]
+enum Either<A, B> {
+    A(A),
+    B(B),
+}
+
+#[derive(PinnedLen)]
+enum Len<A> {
+    A(A),
+    B(Pinned),
+}
+
+trait PinnedName {
+    fn name(self: Pin<&Self>) -> &'static str;
+}
+
+impl<T> PinnedName for future::Ready<T> {
+    fn name(self: Pin<&Self>) -> &'static str {
+        "ready"
+    }
+}
+
+impl<T> PinnedName for future::Pending<T> {
+    fn name(self: Pin<&Self>) -> &'static str {
+        "pending"
+    }
+}
+
+// Only `Future` emits the `Unpin` impl.
+#[derive(Future, PinnedName)]
+enum Both<A, B> {
+    A(A),
+    B(B),
+}
+
+fn assert_unpin<T: Unpin>() {}
+
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+fn main() {
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut cx = Context::from_waker(&waker);
+
+    let mut fut = pin!(Either::<_, future::Pending<u8>>::A(future::ready(1)));
+    assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready(1));
+    let mut fut = pin!(Either::<future::Ready<u8>, _>::B(async { 2 }));
+    assert_eq!(fut.as_mut().poll(&mut cx), Poll::Ready(2));
+
+    assert_unpin::<Either<future::Ready<u8>, future::Pending<u8>>>();
+
+    let len = pin!(Len::A(String::from("ab")));
+    assert_eq!(len.as_ref().len(), 2);
+    let len = pin!(Len::<String>::B(Pinned(3, PhantomPinned)));
+    assert_eq!(len.as_ref().len(), 3);
+
+    let mut both = pin!(Both::<_, future::Pending<u8>>::A(future::ready(4)));
+    assert_eq!(both.as_ref().name(), "ready");
+    assert_eq!(both.as_mut().poll(&mut cx), Poll::Ready(4));
+    let both = pin!(Both::<future::Ready<u8>, _>::B(future::pending::<u8>()));
+    assert_eq!(both.as_ref().name(), "pending");
+    assert_unpin::<Both<future::Ready<u8>, future::Pending<u8>>>();
+}
@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use example_derive::{ForwardIterator, SkipIterator};
+
+/// An iterator that overrides provided methods.
+struct Counter(u8);
+
+impl Iterator for Counter {
+    type Item = u8;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0 += 1;
+        Some(self.0)
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+    fn nth(&mut self, _n: usize) -> Option<Self::Item> {
+        Some(u8::MAX)
+    }
+    fn fold<B, F>(self, init: B, _f: F) -> B
+    where
+        F: FnMut(B, Self::Item) -> B,
+    {
+        init
+    }
+}
+
+#[derive(SkipIterator)]
+enum Skip<A, B> {
+    A(A),
+    B(B),
+}
+
+#[derive(ForwardIterator)]
+enum Forward<A, B> {
+    A(A),
+    B(B),
+}
+
+fn main() {
+    // The default bodies of `Iterator` apply.
+    let mut skip = Skip::<_, std::ops::Range<u8>>::A(Counter(0));
+    assert_eq!(skip.size_hint(), (0, None));
+    assert_eq!(skip.nth(1), Some(2));
+    assert_eq!(skip.by_ref().next(), Some(3));
+    assert_eq!(skip.take(2).fold(0, |sum, x| sum + x), 9);
+
+    // Provided methods are delegated to variants.
+    let mut forward = Forward::<_, std::ops::Range<u8>>::A(Counter(0));
+    assert_eq!(forward.size_hint(), (usize::MAX, None));
+    assert_eq!(forward.nth(1), Some(u8::MAX));
+    assert_eq!(forward.by_ref().next(), Some(1));
+    assert_eq!(forward.fold(0, |sum, x| sum + x), 0);
+    let forward = Forward::<Counter, _>::B(1..4);
+    assert_eq!(forward.fold(0, |sum, x| sum + x), 6);
+}
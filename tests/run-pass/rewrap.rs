@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use example_derive::Resize;
+
+trait Resize: Sized {
+    fn scaled(self, factor: u32) -> Self;
+    fn split(&self) -> Option<(Self, Self)>;
+    fn tiles(&self, n: usize) -> Vec<Self>;
+    fn checked(self, max: u32) -> Result<Self, (u32, Self)>;
+    fn area(&self) -> u32;
+}
+
+#[derive(Debug, PartialEq)]
+struct Square(u32);
+
+impl Resize for Square {
+    fn scaled(self, factor: u32) -> Self {
+        Self(self.0 * factor)
+    }
+    fn split(&self) -> Option<(Self, Self)> {
+        None
+    }
+    fn tiles(&self, n: usize) -> Vec<Self> {
+        (0..n).map(|_| Self(self.0)).collect()
+    }
+    fn checked(self, max: u32) -> Result<Self, (u32, Self)> {
+        if self.area() <= max { Ok(self) } else { Err((self.area() - max, self)) }
+    }
+    fn area(&self) -> u32 {
+        self.0 * self.0
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Rect(u32, u32);
+
+impl Resize for Rect {
+    fn scaled(self, factor: u32) -> Self {
+        Self(self.0 * factor, self.1 * factor)
+    }
+    fn split(&self) -> Option<(Self, Self)> {
+        Some((Self(self.0 / 2, self.1), Self(self.0 - self.0 / 2, self.1)))
+    }
+    fn tiles(&self, _n: usize) -> Vec<Self> {
+        vec![]
+    }
+    fn checked(self, max: u32) -> Result<Self, (u32, Self)> {
+        if self.area() <= max { Ok(self) } else { Err((self.area() - max, self)) }
+    }
+    fn area(&self) -> u32 {
+        self.0 * self.1
+    }
+}
+
+#[derive(Debug, PartialEq, Resize)]
+enum Shape<S, R> {
+    Square(S),
+    Rect { inner: R },
+}
+
+fn main() {
+    type S = Shape<Square, Rect>;
+
+    assert_eq!(S::Square(Square(2)).scaled(3), S::Square(Square(6)));
+    assert_eq!(S::Rect { inner: Rect(1, 2) }.scaled(2), S::Rect { inner: Rect(2, 4) });
+
+    assert_eq!(S::Square(Square(2)).split(), None);
+    assert_eq!(
+        S::Rect { inner: Rect(3, 2) }.split(),
+        Some((S::Rect { inner: Rect(1, 2) }, S::Rect { inner: Rect(2, 2) }))
+    );
+
+    assert_eq!(S::Square(Square(1)).tiles(2), [S::Square(Square(1)), S::Square(Square(1))]);
+    assert_eq!(S::Rect { inner: Rect(1, 1) }.tiles(2), []);
+
+    assert_eq!(S::Square(Square(2)).checked(4), Ok(S::Square(Square(2))));
+    assert_eq!(
+        S::Rect { inner: Rect(2, 3) }.checked(4),
+        Err((2, S::Rect { inner: Rect(2, 3) }))
+    );
+}
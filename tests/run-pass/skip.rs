@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use example_derive::Iterator;
+
+struct NotIterator;
+
+#[derive(Iterator)]
+enum Stream<A, B> {
+    A(A),
+    B(B),
+    #[enum_derive(skip)]
+    Unreachable(NotIterator),
+    #[enum_derive(skip(panic = "closed"))]
+    Closed,
+    #[enum_derive(skip(default))]
+    Empty(NotIterator, u8),
+    #[enum_derive(skip(with = Default::default()))]
+    Done { reason: NotIterator },
+}
+
+fn main() {
+    let a = Stream::<_, std::vec::IntoIter<u8>>::A(0..3_u8);
+    assert_eq!(a.collect::<Vec<_>>(), [0, 1, 2]);
+    let b = Stream::<std::ops::Range<u8>, _>::B(vec![4, 5].into_iter());
+    assert_eq!(b.sum::<u8>(), 9);
+    let empty = Stream::<std::ops::Range<u8>, std::vec::IntoIter<u8>>::Empty(NotIterator, 0);
+    assert_eq!(empty.size_hint(), (0, None));
+    let mut done =
+        Stream::<std::ops::Range<u8>, std::vec::IntoIter<u8>>::Done { reason: NotIterator };
+    assert_eq!(done.next(), None);
+    let closed = Stream::<std::ops::Range<u8>, std::vec::IntoIter<u8>>::Closed;
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || closed.count()));
+    assert!(res.is_err());
+}
@@ -0,0 +1,22 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use example_derive::{ExactSizeIterator, Iterator};
+
+#[derive(Iterator, ExactSizeIterator)]
+struct Wrapper<I>(I);
+
+#[derive(Iterator, ExactSizeIterator)]
+struct Spanned<I> {
+    #[delegate]
+    inner: I,
+    span: (usize, usize),
+}
+
+fn main() {
+    let wrapper = Wrapper(0..3);
+    assert_eq!(wrapper.len(), 3);
+    assert_eq!(wrapper.sum::<u8>(), 3);
+    let spanned = Spanned { inner: vec![4, 5].into_iter(), span: (0, 1) };
+    assert_eq!(spanned.len(), 2);
+    assert_eq!(spanned.sum::<u8>(), 9);
+}
@@ -0,0 +1,23 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use example_derive::DefaultIterator;
+
+#[derive(DefaultIterator)]
+enum Source<A, B> {
+    Empty,
+    File(A),
+    Net(B),
+    Closed(),
+}
+
+fn main() {
+    let empty = Source::<std::ops::Range<u8>, std::vec::IntoIter<u8>>::Empty;
+    assert_eq!(empty.size_hint(), (0, None));
+    assert_eq!(empty.count(), 0);
+    let closed = Source::<std::ops::Range<u8>, std::vec::IntoIter<u8>>::Closed();
+    assert_eq!(closed.count(), 0);
+    let file = Source::<_, std::vec::IntoIter<u8>>::File(0..3);
+    assert_eq!(file.collect::<Vec<_>>(), [0, 1, 2]);
+    let net = Source::<std::ops::Range<u8>, _>::Net(vec![4, 5].into_iter());
+    assert_eq!(net.sum::<u8>(), 9);
+}
@@ -0,0 +1,41 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use example_derive::Name;
+
+trait Name {
+    const NAME: &'static str;
+    const ID: u32;
+    const VERSION: u8;
+    fn name(&self) -> &'static str;
+}
+
+struct Json;
+struct Toml;
+
+impl Name for Json {
+    const NAME: &'static str = "json";
+    const ID: u32 = 1;
+    const VERSION: u8 = 0;
+    fn name(&self) -> &'static str {
+        "json"
+    }
+}
+
+impl Name for Toml {
+    const NAME: &'static str = "toml";
+    const ID: u32 = 1;
+    const VERSION: u8 = 0;
+    fn name(&self) -> &'static str {
+        "toml"
+    }
+}
+
+#[derive(Name)]
+enum Codec<A, B> {
+    A(A),
+    B(B),
+}
+
+const NAME: &str = <Codec<Json, Toml> as Name>::NAME;
+
+fn main() {}
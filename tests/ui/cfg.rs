@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use example_derive::enum_impl;
+
+#[enum_impl(Iterator)]
+enum Many<A, B, C, D, E> { //~ ERROR too many distinct cfg conditions on variants
+    #[cfg(all())]
+    A(A),
+    #[cfg(not(any()))]
+    B(B),
+    #[cfg(any(all()))]
+    C(C),
+    #[cfg(all(all()))]
+    D(D),
+    #[cfg(not(all(any())))]
+    E(E),
+}
+
+#[enum_impl(Iterator)]
+enum Attr<A, B> {
+    A(A),
+    #[cfg_attr()] //~ ERROR cfg_attr on variants is not supported without a predicate
+    B(B),
+}
+
+fn main() {}
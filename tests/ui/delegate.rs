@@ -0,0 +1,28 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use example_derive::Iterator;
+
+#[derive(Iterator)]
+enum Duplicate<A, B> {
+    A(#[delegate] A, #[delegate] A), //~ ERROR duplicate #[delegate] attribute
+    B(B),
+}
+
+#[derive(Iterator)]
+enum Args<A, B> {
+    A(#[delegate(x)] A), //~ ERROR unexpected token in attribute
+    B(B),
+}
+
+#[derive(Iterator)]
+enum Unmarked<A, B> {
+    A(#[delegate] A, u8),
+    B(B, u8), //~ ERROR may not be used on enums with variants with multiple fields
+}
+
+#[derive(Iterator)]
+enum UnmarkedNamed<A> {
+    A { inner: A, meta: u8 }, //~ ERROR may not be used on enums with variants with multiple fields
+}
+
+fn main() {}
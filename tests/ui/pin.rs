@@ -0,0 +1,52 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::{
+    future::Future as _,
+    marker::PhantomPinned,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use example_derive::Future;
+
+#[derive(Future)] //~ ERROR conflicting implementations of trait `__MustNotImplDrop`
+enum Drop<A, B> {
+    A(A),
+    B(B),
+}
+
+impl<A, B> std::ops::Drop for Drop<A, B> {
+    fn drop(&mut self) {}
+}
+
+#[derive(Future)] //~ ERROR conflicting implementations of trait `Unpin`
+enum Unpin<A, B> {
+    A(A),
+    B(B),
+}
+
+impl<A, B> std::marker::Unpin for Unpin<A, B> {}
+
+struct Pinned(PhantomPinned);
+
+impl std::future::Future for Pinned {
+    type Output = ();
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Poll::Ready(())
+    }
+}
+
+#[derive(Future)]
+enum Either<A, B> {
+    A(A),
+    B(B),
+}
+
+fn assert_unpin<T: std::marker::Unpin>() {}
+
+fn main() {
+    assert_unpin::<Either<Pinned, std::future::Ready<()>>>(); //~ ERROR cannot be unpinned
+
+    let mut fut = Either::<Pinned, Pinned>::A(Pinned(PhantomPinned));
+    let _ = Pin::new(&mut fut).poll(&mut Context::from_waker(std::task::Waker::noop())); //~ ERROR cannot be unpinned
+}
@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::pin::Pin;
+
+use example_derive::PinnedName;
+
+trait PinnedName {
+    fn name(self: Pin<&Self>) -> &'static str;
+}
+
+// No derive emits the `Unpin` impl.
+#[derive(PinnedName)] //~ ERROR cannot find value `__unpin_impl_false_requires_another_impl_to_emit_Unpin_for_Either`
+enum Either<A, B> {
+    A(A),
+    B(B),
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use example_derive::Resize;
+
+#[derive(Resize)] //~ ERROR returns `Self`, but a variant has fields other than the field to delegate to
+enum Tagged<A, B> {
+    A(#[delegate] A, u8),
+    B(B),
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use example_derive::{BoxClone, FirstTile};
+
+#[derive(BoxClone)] //~ ERROR returns `Box < Self >`, which cannot be converted from the results of the variants
+enum Either<A, B> {
+    A(A),
+    B(B),
+}
+
+#[derive(FirstTile)] //~ ERROR returns `Option < < Vec < Self > as IntoIterator > :: Item >`
+enum Tiles<A, B> {
+    A(A),
+    B(B),
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::sync::Arc;
+
+use example_derive::Spawn;
+
+trait Spawn {
+    fn spawn(self: Arc<Self>);
+    fn name(&self) -> &'static str;
+}
+
+#[derive(Spawn)] //~ ERROR cannot be delegated to the variants
+enum Either<A, B> {
+    A(A),
+    B(B),
+}
+
+fn main() {}
@@ -0,0 +1,41 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use example_derive::Iterator;
+
+#[derive(Iterator)]
+enum Unknown<A, B> {
+    A(A),
+    #[enum_derive(ignore)] //~ ERROR unknown #[enum_derive] option
+    B(B),
+}
+
+#[derive(Iterator)]
+enum Fallback<A, B> {
+    A(A),
+    #[enum_derive(skip(todo))] //~ ERROR expected `unreachable`, `panic = "..."`, `default`, or `with = ...`
+    B(B),
+}
+
+#[derive(Iterator)]
+enum Multiple<A, B> {
+    A(A),
+    #[enum_derive(skip(default, unreachable))] //~ ERROR multiple fallbacks for `skip`
+    B(B),
+}
+
+#[derive(Iterator)]
+enum Duplicate<A, B> {
+    A(A),
+    #[enum_derive(skip)]
+    #[enum_derive(skip)] //~ ERROR duplicate `skip` option
+    B(B),
+}
+
+#[derive(Iterator)]
+enum AllSkipped<A> {
+    //~^ ERROR may not be used on enums without variants with fields
+    #[enum_derive(skip)]
+    A(A),
+}
+
+fn main() {}
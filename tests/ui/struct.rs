@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use example_derive::Iterator;
+
+#[derive(Iterator)]
+struct Unit; //~ ERROR may not be used on structs without fields
+
+#[derive(Iterator)]
+struct Empty {} //~ ERROR may not be used on structs without fields
+
+#[derive(Iterator)]
+struct Multiple<I>(I, u8); //~ ERROR may not be used on structs with multiple fields
+
+fn main() {}
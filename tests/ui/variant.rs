@@ -8,7 +8,8 @@ enum EnumWZV {} //~ ERROR may not be used on enums without variants
 #[derive(Iterator)]
 enum EnumWD {
     A = 2, //~ ERROR may not be used on enums with discriminants
-    B,
+    //~^ ERROR may not be used on enums with variants with zero fields
+    B, //~ ERROR may not be used on enums with variants with zero fields
 }
 
 #[derive(Iterator)]
@@ -30,9 +31,13 @@ enum EnumWMF<A, B> {
 }
 
 #[derive(Iterator)]
-enum EnumWNF<A, B> {
-    A { x: A }, //~ ERROR may not be used on enums with variants with named fields
-    B(B),
+#[repr(u8)]
+enum EnumMultiple<A, B> {
+    A, //~ ERROR may not be used on enums with variants with zero fields
+    B(A, B) = 2, //~ ERROR may not be used on enums with discriminants
+    //~^ ERROR may not be used on enums with variants with multiple fields
+    C(#[delegate] A, #[delegate] B), //~ ERROR duplicate #[delegate] attribute
+    D(B),
 }
 
 fn main() {}